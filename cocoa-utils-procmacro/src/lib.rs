@@ -133,6 +133,17 @@ fn is_id(ty: &syn::Type) -> bool {
     }
 }
 
+fn is_system_time(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(ref path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "SystemTime"),
+        _ => false,
+    }
+}
+
 fn is_value_type(ty: &syn::Type) -> bool {
     match ty {
         syn::Type::Path(ref path) => {
@@ -167,6 +178,8 @@ fn append_instance_property(fn_item: &mut syn::ItemFn, meta: &CocoaInstancePrope
         append_string_instance_property(fn_item, selector, optional);
     } else if is_id(return_type) {
         append_id_instance_property(fn_item, selector, optional);
+    } else if is_system_time(return_type) {
+        append_system_time_instance_property(fn_item, selector, optional);
     } else if is_value_type(return_type) {
         if optional {
             panic!("value types cannot be optional");
@@ -282,6 +295,40 @@ fn append_id_instance_property(
         .push(syn::parse2(return_statement).unwrap());
 }
 
+fn append_system_time_instance_property(
+    fn_item: &mut syn::ItemFn,
+    selector: &proc_macro2::Ident,
+    optional: bool,
+) {
+    let return_statement = if optional {
+        quote! {{
+            let ptr: Id = self.ptr();
+            let ptr: Id = msg_send![ptr, #selector];
+
+            let result = Date::from_ptr(ptr).and_then(|date| date.to_system_time());
+            return result;
+        }}
+    } else {
+        quote! {{
+            let ptr: Id = self.ptr();
+            let ptr: Id = msg_send![ptr, #selector];
+
+            if ptr.is_null() {
+                panic!("Property getter returned a null ptr. You may need to wrap the return type in Option<T>.");
+            }
+
+            return Date::from_ptr(ptr)
+                .unwrap()
+                .to_system_time()
+                .expect("Property getter returned a date outside the range of SystemTime. You may need to wrap the return type in Option<T>.");
+        }}
+    };
+    fn_item
+        .block
+        .stmts
+        .push(syn::parse2(return_statement).unwrap());
+}
+
 fn append_cocoatype_instance_property(
     fn_item: &mut syn::ItemFn,
     return_type: &syn::Type,
@@ -352,6 +399,8 @@ fn append_type_property(fn_item: &mut syn::ItemFn, meta: &CocoaTypePropertyMeta)
         append_string_type_property(fn_item, class_name, selector, optional);
     } else if is_id(return_type) {
         append_id_type_property(fn_item, class_name, selector, optional);
+    } else if is_system_time(return_type) {
+        append_system_time_type_property(fn_item, class_name, selector, optional);
     } else {
         append_cocoatype_type_property(fn_item, return_type, class_name, selector, optional);
     }
@@ -439,6 +488,39 @@ fn append_id_type_property(
         .push(syn::parse2(return_statement).unwrap());
 }
 
+fn append_system_time_type_property(
+    fn_item: &mut syn::ItemFn,
+    class_name: &proc_macro2::Ident,
+    selector: &proc_macro2::Ident,
+    optional: bool,
+) {
+    let return_statement = if optional {
+        quote! {{
+            let ptr: Id = msg_send![class!(#class_name), #selector];
+
+            let result = Date::from_ptr(ptr).and_then(|date| date.to_system_time());
+            return result;
+        }}
+    } else {
+        quote! {{
+            let ptr: Id = msg_send![class!(#class_name), #selector];
+
+            if ptr.is_null() {
+                panic!("Property getter returned a null ptr. You may need to wrap the return type in Option<T>.");
+            }
+
+            return Date::from_ptr(ptr)
+                .unwrap()
+                .to_system_time()
+                .expect("Property getter returned a date outside the range of SystemTime. You may need to wrap the return type in Option<T>.");
+        }}
+    };
+    fn_item
+        .block
+        .stmts
+        .push(syn::parse2(return_statement).unwrap());
+}
+
 fn append_cocoatype_type_property(
     fn_item: &mut syn::ItemFn,
     return_type: &syn::Type,
@@ -1,8 +1,9 @@
-use cocoa_utils::AppNotification;
-use cocoa_utils::NSAppWatcher;
-use crossbeam::channel::unbounded;
-
+#[cfg(target_os = "macos")]
 fn main() {
+    use cocoa_utils::AppNotification;
+    use cocoa_utils::NSAppWatcher;
+    use crossbeam::channel::unbounded;
+
     let (s, r) = unbounded::<AppNotification>();
 
     let thr = std::thread::spawn(move || {
@@ -15,3 +16,8 @@ fn main() {
 
     thr.join().unwrap();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("The app_watcher example requires macOS.");
+}
@@ -0,0 +1,253 @@
+use std::path::PathBuf;
+
+use crate::{NotificationType, RunningAppInfo};
+
+#[cfg(target_os = "macos")]
+use crate::{plist, prelude::*, AppNotification};
+#[cfg(target_os = "macos")]
+use cocoa::base::nil;
+#[cfg(target_os = "macos")]
+use cocoa::foundation::NSAutoreleasePool;
+
+/// A Carbon process serial number, as found in launch and termination notifications.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessSerialNumber {
+    pub high: u32,
+    pub low: u32,
+}
+
+/// The application details that launch and termination notifications carry in their `userInfo`,
+/// besides the `NSRunningApplication`.
+///
+/// Every field is optional, since macOS doesn't guarantee any of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LaunchInfo {
+    /// `NSApplicationPath`, the path of the application bundle.
+    pub path: Option<PathBuf>,
+    /// `NSApplicationName`.
+    pub name: Option<String>,
+    /// `NSApplicationProcessSerialNumberHigh` and `NSApplicationProcessSerialNumberLow`.
+    pub psn: Option<ProcessSerialNumber>,
+}
+
+/// An application notification, with the payload specific to its type.
+///
+/// [`AppNotification`](crate::AppNotification) is the flattened form of this enum, and can be
+/// converted from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "notification_type", rename_all = "lowercase")
+)]
+pub enum AppEvent {
+    Launching {
+        app: RunningAppInfo,
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        info: LaunchInfo,
+    },
+    Launched {
+        app: RunningAppInfo,
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        info: LaunchInfo,
+    },
+    Terminated {
+        app: RunningAppInfo,
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        info: LaunchInfo,
+        /// `NSWorkspaceExitStatusKey`, the process's exit status.
+        exit_status: Option<i32>,
+    },
+    Hidden {
+        app: RunningAppInfo,
+    },
+    Unhidden {
+        app: RunningAppInfo,
+    },
+    Activated {
+        app: RunningAppInfo,
+    },
+    Deactivated {
+        app: RunningAppInfo,
+    },
+    /// The application was already running when the watcher started.
+    #[cfg_attr(feature = "serde", serde(rename = "already_running"))]
+    AlreadyRunning {
+        app: RunningAppInfo,
+        hidden: bool,
+        finished_launching: bool,
+    },
+}
+
+impl AppEvent {
+    pub fn notification_type(&self) -> NotificationType {
+        match self {
+            AppEvent::Launching { .. } => NotificationType::Launching,
+            AppEvent::Launched { .. } => NotificationType::Launched,
+            AppEvent::Terminated { .. } => NotificationType::Terminated,
+            AppEvent::Hidden { .. } => NotificationType::Hidden,
+            AppEvent::Unhidden { .. } => NotificationType::Unhidden,
+            AppEvent::Activated { .. } => NotificationType::Activated,
+            AppEvent::Deactivated { .. } => NotificationType::Deactivated,
+            AppEvent::AlreadyRunning { .. } => NotificationType::AlreadyRunning,
+        }
+    }
+
+    /// The application the notification is about.
+    pub fn app(&self) -> &RunningAppInfo {
+        match self {
+            AppEvent::Launching { app, .. }
+            | AppEvent::Launched { app, .. }
+            | AppEvent::Terminated { app, .. }
+            | AppEvent::Hidden { app }
+            | AppEvent::Unhidden { app }
+            | AppEvent::Activated { app }
+            | AppEvent::Deactivated { app }
+            | AppEvent::AlreadyRunning { app, .. } => app,
+        }
+    }
+
+    pub fn into_app(self) -> RunningAppInfo {
+        match self {
+            AppEvent::Launching { app, .. }
+            | AppEvent::Launched { app, .. }
+            | AppEvent::Terminated { app, .. }
+            | AppEvent::Hidden { app }
+            | AppEvent::Unhidden { app }
+            | AppEvent::Activated { app }
+            | AppEvent::Deactivated { app }
+            | AppEvent::AlreadyRunning { app, .. } => app,
+        }
+    }
+
+    /// The launch details of launch and termination notifications.
+    pub fn launch_info(&self) -> Option<&LaunchInfo> {
+        match self {
+            AppEvent::Launching { info, .. }
+            | AppEvent::Launched { info, .. }
+            | AppEvent::Terminated { info, .. } => Some(info),
+            _ => None,
+        }
+    }
+
+    /// Builds an event without any type-specific payload, e.g. from an `AppNotification`.
+    pub fn from_parts(notification_type: NotificationType, app: RunningAppInfo) -> AppEvent {
+        match notification_type {
+            NotificationType::Launching => AppEvent::Launching {
+                app,
+                info: LaunchInfo::default(),
+            },
+            NotificationType::Launched => AppEvent::Launched {
+                app,
+                info: LaunchInfo::default(),
+            },
+            NotificationType::Terminated => AppEvent::Terminated {
+                app,
+                info: LaunchInfo::default(),
+                exit_status: None,
+            },
+            NotificationType::Hidden => AppEvent::Hidden { app },
+            NotificationType::Unhidden => AppEvent::Unhidden { app },
+            NotificationType::Activated => AppEvent::Activated { app },
+            NotificationType::Deactivated => AppEvent::Deactivated { app },
+            NotificationType::AlreadyRunning => AppEvent::AlreadyRunning {
+                app,
+                hidden: false,
+                finished_launching: true,
+            },
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl AppEvent {
+    /// Parses an `NSWorkspace` application notification, keeping the payload of its `userInfo`.
+    ///
+    /// Every notification's `userInfo` holds the `NSRunningApplication` under
+    /// `NSWorkspaceApplicationKey`. Launch and termination notifications also hold
+    /// `NSApplicationPath`, `NSApplicationName`, `NSApplicationBundleIdentifier`,
+    /// `NSApplicationProcessIdentifier` and `NSApplicationProcessSerialNumberHigh`/`Low`, and
+    /// termination notifications hold `NSWorkspaceExitStatusKey`.
+    pub fn parse_notification(
+        notification: &Notification,
+        notification_type: NotificationType,
+    ) -> Result<AppEvent, CocoaError> {
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+
+            let event =
+                AppNotification::application_from_user_info(notification).map(|application| {
+                    let app = RunningAppInfo::from(application);
+
+                    match notification_type {
+                        NotificationType::Launching => AppEvent::Launching {
+                            app,
+                            info: launch_info(notification),
+                        },
+                        NotificationType::Launched => AppEvent::Launched {
+                            app,
+                            info: launch_info(notification),
+                        },
+                        NotificationType::Terminated => AppEvent::Terminated {
+                            app,
+                            info: launch_info(notification),
+                            exit_status: user_info_integer(
+                                notification,
+                                "NSWorkspaceExitStatusKey",
+                            )
+                            .and_then(|status| i32::try_from(status).ok()),
+                        },
+                        _ => AppEvent::from_parts(notification_type, app),
+                    }
+                });
+
+            pool.drain();
+
+            event
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn user_info_value(notification: &Notification, key: &str) -> Option<plist::Value> {
+    let user_info = notification.user_info()?;
+    unsafe { plist::Value::from_id(user_info.get_id(key)?) }
+}
+
+#[cfg(target_os = "macos")]
+fn user_info_integer(notification: &Notification, key: &str) -> Option<i64> {
+    match user_info_value(notification, key)? {
+        plist::Value::Integer(value) => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn user_info_string(notification: &Notification, key: &str) -> Option<String> {
+    match user_info_value(notification, key)? {
+        plist::Value::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn launch_info(notification: &Notification) -> LaunchInfo {
+    let psn_part =
+        |key| user_info_integer(notification, key).and_then(|value| u32::try_from(value).ok());
+
+    let psn = match (
+        psn_part("NSApplicationProcessSerialNumberHigh"),
+        psn_part("NSApplicationProcessSerialNumberLow"),
+    ) {
+        (Some(high), Some(low)) => Some(ProcessSerialNumber { high, low }),
+        _ => None,
+    };
+
+    LaunchInfo {
+        path: user_info_string(notification, "NSApplicationPath").map(PathBuf::from),
+        name: user_info_string(notification, "NSApplicationName"),
+        psn,
+    }
+}
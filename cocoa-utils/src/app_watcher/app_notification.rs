@@ -1,16 +1,63 @@
-use crate::prelude::*;
-use cocoa::base::nil;
-use cocoa::foundation::NSAutoreleasePool;
-use objc::{msg_send, sel, sel_impl};
+use std::path::PathBuf;
 
-#[derive(Debug, Clone)]
+use crate::{AppEvent, EventStamp, NotificationType};
+
+#[cfg(target_os = "macos")]
+use crate::{downcast, prelude::*};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RunningAppInfo {
     pub pid: usize,
     pub localized_name: Option<String>,
     pub bundle_identifier: Option<String>,
+    /// The absolute string of the application's bundle URL, e.g.
+    /// `file:///Applications/Safari.app/`.
     pub bundle_url: Option<String>,
 }
 
+impl RunningAppInfo {
+    /// The path of the application's bundle, or `None` if its bundle URL isn't a `file:` URL.
+    pub fn bundle_path(&self) -> Option<PathBuf> {
+        let url = self.bundle_url.as_deref()?;
+        let path = url
+            .strip_prefix("file://")
+            .map(|path| path.strip_prefix("localhost").unwrap_or(path))
+            .filter(|path| path.starts_with('/'))?;
+
+        let path = percent_decode(path)?;
+        let path = match path.trim_end_matches('/') {
+            "" => "/",
+            path => path,
+        };
+
+        Some(PathBuf::from(path))
+    }
+}
+
+/// Decodes the `%XX` escapes of a URL's path, or returns `None` if one is malformed or the
+/// result isn't UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(target_os = "macos")]
 impl From<RunningApplication> for RunningAppInfo {
     fn from(running_application: RunningApplication) -> Self {
         unsafe {
@@ -31,120 +78,186 @@ impl From<RunningApplication> for RunningAppInfo {
     }
 }
 
-#[derive(Debug, Clone)]
+/// An application notification received by a watcher.
+///
+/// Outside this crate, create one with [`AppNotification::new`], since more fields may be added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct AppNotification {
     pub notification_type: NotificationType,
     pub app: RunningAppInfo,
+    /// When the watcher received the notification. `None` for notifications that didn't come
+    /// from a watcher.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub stamp: Option<EventStamp>,
 }
 
 impl AppNotification {
-    /// Parses a notification from an `Id` to an `AppNotification`.
-    ///
-    /// # Safety
-    ///
-    /// This function is unsafe because it dereferences `notification`, which is a raw pointer.
+    /// Creates a notification without a stamp.
+    pub fn new(notification_type: NotificationType, app: RunningAppInfo) -> AppNotification {
+        AppNotification {
+            notification_type,
+            app,
+            stamp: None,
+        }
+    }
+}
+
+impl From<AppEvent> for AppNotification {
+    fn from(event: AppEvent) -> Self {
+        let notification_type = event.notification_type();
+        AppNotification::new(notification_type, event.into_app())
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl AppNotification {
+    /// Parses an `NSWorkspace` application notification to an `AppNotification`.
     ///
-    pub unsafe fn parse_notification(
-        notification: Id,
+    /// This is the flattened form of [`AppEvent::parse_notification`].
+    pub fn parse_notification(
+        notification: &Notification,
         notification_type: NotificationType,
-    ) -> Result<AppNotification, String> {
-        unsafe {
-            let pool = NSAutoreleasePool::new(nil);
-
-            let dict: Id = msg_send![notification, userInfo];
-
-            {
-                let dict = Dict::from_ptr(dict).unwrap();
-
-                let application = dict
-                    .get_id("NSWorkspaceApplicationKey")
-                    .map(|ptr| RunningApplication::from_ptr(ptr).unwrap())
-                    .unwrap();
-
-                let notif = match notification_type {
-                    NotificationType::Launched => {
-                        // let application_path = dict.get_string("NSApplicationPath").unwrap();
-                        // let bundle_identifier =
-                        //     dict.get_string("NSApplicationBundleIdentifier").unwrap();
-                        // let process_identifier =
-                        //     dict.get_value::<i32>("NSApplicationProcessIdentifier");
-                        // let pid = application.process_identifier();
-                        // let application_name = dict.get_string("NSApplicationName").unwrap();
-
-                        // let n = application_name.replace(".", "_").to_string();
-
-                        // let user_dir = std::env::var("HOME").unwrap();
-
-                        // let p = format!("{user_dir}/.hyperkey/app_icons/{}.tiff", n);
-                        // dbg!(&p);
-
-                        // dbg!(application.save_icon_to_file(&p));
-
-                        /*
-                        - [NSApplicationPath] (__NSCFString)
-                        - [NSWorkspaceApplicationKey] (NSRunningApplication)
-                        - [NSApplicationBundleIdentifier] (__NSCFString)
-                        - [NSApplicationProcessSerialNumberLow] (__NSCFNumber)
-                        - [NSApplicationProcessIdentifier] (__NSCFNumber)
-                        - [NSApplicationProcessSerialNumberHigh] (__NSCFNumber)
-                        - [NSApplicationName] (NSTaggedPointerString)
-                        */
-                        AppNotification {
-                            notification_type,
-                            app: application.into(),
-                        }
-                    }
-                    NotificationType::Terminated => {
-                        /*
-                        - [NSApplicationPath] (__NSCFString)
-                        - [NSWorkspaceApplicationKey] (NSRunningApplication)
-                        - [NSApplicationBundleIdentifier] (__NSCFString)
-                        - [NSWorkspaceExitStatusKey] (__NSCFNumber)
-                        - [NSApplicationProcessIdentifier] (__NSCFNumber)
-                        - [NSApplicationProcessSerialNumberLow] (__NSCFNumber)
-                        - [NSApplicationProcessSerialNumberHigh] (__NSCFNumber)
-                        - [NSApplicationName] (NSTaggedPointerString)
-                        */
-                        AppNotification {
-                            notification_type,
-                            app: application.into(),
-                        }
-                    }
-                    NotificationType::Launching => {
-                        // - [NSApplicationPath] (__NSCFString)
-                        // - [NSWorkspaceApplicationKey] (NSRunningApplication)
-                        // - [NSApplicationBundleIdentifier] (__NSCFString)
-                        // - [NSApplicationProcessSerialNumberLow] (__NSCFNumber)
-                        // - [NSApplicationProcessIdentifier] (__NSCFNumber)
-                        // - [NSApplicationProcessSerialNumberHigh] (__NSCFNumber)
-                        // - [NSApplicationName] (NSTaggedPointerString)
-                        AppNotification {
-                            notification_type,
-                            app: application.into(),
-                        }
-                    }
-                    NotificationType::Hidden => AppNotification {
-                        notification_type,
-                        app: application.into(),
-                    },
-                    NotificationType::Unhidden => AppNotification {
-                        notification_type,
-                        app: application.into(),
-                    },
-                    NotificationType::Activated => AppNotification {
-                        notification_type,
-                        app: application.into(),
-                    },
-                    NotificationType::Deactivated => AppNotification {
-                        notification_type,
-                        app: application.into(),
-                    },
-                };
-
-                pool.drain();
-
-                Ok(notif)
-            }
+    ) -> Result<AppNotification, CocoaError> {
+        AppEvent::parse_notification(notification, notification_type).map(AppNotification::from)
+    }
+
+    /// Gets the `NSRunningApplication` from a workspace notification's `userInfo`.
+    pub(crate) unsafe fn application_from_user_info(
+        notification: &Notification,
+    ) -> Result<RunningApplication, CocoaError> {
+        let user_info = notification
+            .user_info()
+            .ok_or_else(|| CocoaError::nil_return("userInfo"))?;
+
+        let application = user_info
+            .get_id("NSWorkspaceApplicationKey")
+            .ok_or_else(|| CocoaError::nil_return("userInfo[NSWorkspaceApplicationKey]"))?;
+
+        downcast(application, "NSRunningApplication")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle_path(bundle_url: &str) -> Option<PathBuf> {
+        RunningAppInfo {
+            pid: 42,
+            localized_name: None,
+            bundle_identifier: None,
+            bundle_url: Some(bundle_url.to_string()),
+        }
+        .bundle_path()
+    }
+
+    #[test]
+    fn bundle_path_from_url() {
+        assert_eq!(
+            bundle_path("file:///Applications/Safari.app/"),
+            Some(PathBuf::from("/Applications/Safari.app"))
+        );
+        assert_eq!(
+            bundle_path("file://localhost/Applications/My%20App%C3%A9.app/"),
+            Some(PathBuf::from("/Applications/My Appé.app"))
+        );
+        assert_eq!(bundle_path("file:///"), Some(PathBuf::from("/")));
+
+        for url in [
+            "https://example.com/App.app/",
+            "file://server/App.app/",
+            "file:///App%2.app/",
+            "file:///App%+1.app/",
+            "file:///App%FF.app/",
+        ] {
+            assert_eq!(bundle_path(url), None, "{url}");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn safari(notification_type: NotificationType, stamp: Option<EventStamp>) -> AppNotification {
+        AppNotification {
+            notification_type,
+            app: RunningAppInfo {
+                pid: 42,
+                localized_name: Some("Safari".to_string()),
+                bundle_identifier: Some("com.apple.Safari".to_string()),
+                bundle_url: Some("file:///Applications/Safari.app/".to_string()),
+            },
+            stamp,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_json() {
+        let notification = safari(NotificationType::Launched, None);
+        let json = serde_json::to_string(&notification).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({
+                "notification_type": "launched",
+                "app": {
+                    "pid": 42,
+                    "localized_name": "Safari",
+                    "bundle_identifier": "com.apple.Safari",
+                    "bundle_url": "file:///Applications/Safari.app/",
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_str::<AppNotification>(&json).unwrap(),
+            notification
+        );
+
+        let stamped = safari(NotificationType::Activated, Some(EventStamp::now(7)));
+        let json = serde_json::to_string(&stamped).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AppNotification>(&json).unwrap(),
+            stamped
+        );
+
+        for notification_type in NotificationType::ALL {
+            let json = serde_json::to_string(&notification_type).unwrap();
+            assert!(serde_json::from_str::<NotificationType>(&json).unwrap() == notification_type);
         }
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_plist_values() {
+        use crate::plist::{from_value, to_value, Value};
+
+        for notification_type in NotificationType::ALL
+            .into_iter()
+            .chain([NotificationType::AlreadyRunning])
+        {
+            let value = to_value(&notification_type).unwrap();
+            assert_eq!(value, Value::String(notification_type.as_str().to_string()));
+            assert!(from_value::<NotificationType>(value).unwrap() == notification_type);
+        }
+
+        let app = RunningAppInfo {
+            pid: 7,
+            localized_name: None,
+            bundle_identifier: None,
+            bundle_url: None,
+        };
+        assert_eq!(
+            from_value::<RunningAppInfo>(to_value(&app).unwrap()).unwrap(),
+            app
+        );
+
+        let stamped = safari(NotificationType::Hidden, Some(EventStamp::now(1)));
+        assert_eq!(
+            from_value::<AppNotification>(to_value(&stamped).unwrap()).unwrap(),
+            stamped
+        );
+    }
 }
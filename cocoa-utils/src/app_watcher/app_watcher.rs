@@ -1,8 +1,16 @@
 use crate::{notification_type::NotificationType, object::Id};
-use crate::{prelude::*, AppNotification};
-use cocoa::appkit::{NSApp, NSApplication};
-use crossbeam::channel::Sender;
-use lazy_static::lazy_static;
+use crate::{
+    prelude::*, AppEvent, AppFilter, AppNotification, AppWatcherBuilder, AppWatcherError,
+    EventSink, EventStamp,
+};
+use cocoa::appkit::{
+    NSApp, NSApplication, NSEvent, NSEventModifierFlags, NSEventSubtype, NSEventType,
+};
+use cocoa::base::{nil, BOOL, NO, YES};
+use cocoa::foundation::{NSAutoreleasePool, NSPoint};
+use core_foundation::runloop::{
+    kCFRunLoopDefaultMode, CFRunLoop, CFRunLoopTimer, CFRunLoopTimerRef,
+};
 use objc::{
     class,
     declare::ClassDecl,
@@ -10,78 +18,132 @@ use objc::{
     runtime::{Object, Sel},
     sel, sel_impl,
 };
-use objc::{Encode, Encoding};
-use std::sync::atomic::AtomicBool;
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Once, PoisonError, TryLockError};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
-lazy_static! {
-    static ref APP_WATCHER_DECLARED: AtomicBool = AtomicBool::new(false);
-}
+const STATE_IVAR: &str = "watcherState";
 
-struct SenderWrap<T>(*const Sender<T>);
+/// `+[NSDate distantFuture]`, as a `CFAbsoluteTime`.
+const DISTANT_FUTURE: f64 = 63_113_904_000.0;
 
-unsafe impl<T> Encode for SenderWrap<T> {
-    fn encode() -> Encoding {
-        unsafe { Encoding::from_str("?") }
-    }
-}
+static DECLARE_APP_WATCHER: Once = Once::new();
 
-fn ensure_declared<T>() {
-    if APP_WATCHER_DECLARED.load(std::sync::atomic::Ordering::Relaxed) {
-        return;
-    }
+fn ensure_declared() {
+    DECLARE_APP_WATCHER.call_once(|| {
+        let mut cls = ClassDecl::new(NSAppWatcher::class_name(), class!(NSObject)).unwrap();
 
-    let mut cls = ClassDecl::new(NSAppWatcher::class_name(), class!(NSObject)).unwrap();
+        unsafe {
+            cls.add_method(
+                sel!(applicationWillLaunch:),
+                application_will_launch as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(
+                sel!(applicationLaunched:),
+                application_launched as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(
+                sel!(applicationTerminated:),
+                application_terminated as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(
+                sel!(applicationHidden:),
+                application_hidden as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(
+                sel!(applicationUnhidden:),
+                application_unhidden as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(
+                sel!(applicationActivated:),
+                application_activated as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(
+                sel!(applicationDeactivated:),
+                application_deactivated as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(
+                sel!(applicationAlreadyRunning:),
+                application_already_running as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(sel!(dealloc), dealloc as extern "C" fn(&Object, Sel));
+
+            cls.add_ivar::<*mut c_void>(STATE_IVAR);
+        }
 
-    unsafe {
-        cls.add_method(
-            sel!(applicationWillLaunch:),
-            application_will_launch as extern "C" fn(&Object, Sel, Id),
-        );
-        cls.add_method(
-            sel!(applicationLaunched:),
-            application_launched as extern "C" fn(&Object, Sel, Id),
-        );
-        cls.add_method(
-            sel!(applicationTerminated:),
-            application_terminated as extern "C" fn(&Object, Sel, Id),
-        );
-        cls.add_method(
-            sel!(applicationHidden:),
-            application_hidden as extern "C" fn(&Object, Sel, Id),
-        );
-        cls.add_method(
-            sel!(applicationUnhidden:),
-            application_unhidden as extern "C" fn(&Object, Sel, Id),
-        );
-        cls.add_method(
-            sel!(applicationActivated:),
-            application_activated as extern "C" fn(&Object, Sel, Id),
-        );
-        cls.add_method(
-            sel!(applicationDeactivated:),
-            application_deactivated as extern "C" fn(&Object, Sel, Id),
-        );
-
-        // cls.add_ivar::<AppWatcherEvent>("callbackFunctionPtr");
-        cls.add_ivar::<SenderWrap<T>>("channelSenderPtr");
-    }
-
-    cls.register();
-
-    APP_WATCHER_DECLARED.store(true, std::sync::atomic::Ordering::Relaxed);
+        cls.register();
+    });
 }
 
 fn invoke_sender(app_watcher: &Object, notification_type: NotificationType, notification: Id) {
-    unsafe {
-        let app_watcher: Id = app_watcher as *const _ as Id;
-        let mut watcher = NSAppWatcher::from_ptr(app_watcher).unwrap();
+    let state = unsafe {
+        let state = *app_watcher.get_ivar::<*mut c_void>(STATE_IVAR) as *const WatcherState;
+
+        match state.as_ref() {
+            Some(state) => state,
+            None => return,
+        }
+    };
+
+    // Unwinding into Objective-C is undefined behaviour, so panics (e.g. in the sink) are caught
+    // here and reported to the sink instead.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        if !state.running.load(Ordering::SeqCst) {
+            return;
+        }
+
+        // Stamp the notification before anything else, so the time is as close as possible to its
+        // delivery. The sequence number is assigned once it's queued.
+        let stamp = EventStamp::now(0);
+
+        let Some(notification) = (unsafe { Notification::from_ptr(notification) }) else {
+            return;
+        };
+
+        // Filter on the `NSRunningApplication` so unwanted notifications are never parsed. If it's
+        // missing, parsing reports the error.
+        if !state.filter.is_empty() {
+            let application = unsafe { AppNotification::application_from_user_info(&notification) };
+
+            if let Ok(application) = application {
+                if !state.filter.matches_application(&application) {
+                    return;
+                }
+            }
+        }
 
-        let app_notification =
-            AppNotification::parse_notification(notification, notification_type).unwrap();
+        match AppEvent::parse_notification(&notification, notification_type) {
+            Ok(event) => state.push(SinkEvent::Event(event, stamp)),
+            Err(error) => state.push(SinkEvent::Error(AppWatcherError::Parse {
+                notification_type,
+                error,
+            })),
+        }
+    }));
+
+    if let Err(payload) = result {
+        let error = AppWatcherError::Panic {
+            message: panic_message(payload.as_ref()),
+        };
+
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            state.push(SinkEvent::Error(error));
+        }));
+    }
+}
 
-        let sender = watcher.get_sender();
-        let sender: &Sender<AppNotification> = &*(sender.0);
-        sender.send(app_notification).unwrap();
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
     }
 }
 
@@ -113,6 +175,449 @@ extern "C" fn application_deactivated(app_watcher: &Object, _cmd: Sel, notificat
     invoke_sender(app_watcher, NotificationType::Deactivated, notification);
 }
 
+extern "C" fn application_already_running(app_watcher: &Object, _cmd: Sel, notification: Id) {
+    invoke_sender(app_watcher, NotificationType::AlreadyRunning, notification);
+}
+
+/// Releases the watcher object's reference to the shared state.
+extern "C" fn dealloc(app_watcher: &Object, _cmd: Sel) {
+    unsafe {
+        let state = *app_watcher.get_ivar::<*mut c_void>(STATE_IVAR) as *const WatcherState;
+
+        if !state.is_null() {
+            drop(Arc::from_raw(state));
+        }
+
+        let _: () = msg_send![super(app_watcher, class!(NSObject)), dealloc];
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Stops the application's event loop, which may be running on another thread.
+unsafe fn stop_app() {
+    let app = NSApp();
+    let is_main_thread: BOOL = msg_send![class!(NSThread), isMainThread];
+
+    if is_main_thread == YES {
+        app.stop_(nil);
+    } else {
+        let _: () = msg_send![app, performSelectorOnMainThread:sel!(stop:)
+                                                    withObject:nil
+                                                 waitUntilDone:YES];
+    }
+
+    // `stop:` only takes effect once the event loop has handled another event.
+    let event = NSEvent::otherEventWithType_location_modifierFlags_timestamp_windowNumber_context_subtype_data1_data2_(
+        nil,
+        NSEventType::NSApplicationDefined,
+        NSPoint::new(0.0, 0.0),
+        NSEventModifierFlags::empty(),
+        0.0,
+        0,
+        nil,
+        NSEventSubtype::NSWindowExposedEventType,
+        0,
+        0,
+    );
+    app.postEvent_atStart_(event, NO);
+}
+
+extern "C" fn keep_alive(_timer: CFRunLoopTimerRef, _info: *mut c_void) {}
+
+/// Returns an `AlreadyRunning` event for each running application that passes `filter`, followed
+/// by an `Activated` event for the frontmost one if `notification_types` includes `Activated`.
+fn take_snapshot(filter: &AppFilter, notification_types: &[NotificationType]) -> Vec<AppEvent> {
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let workspace = Workspace::shared_workspace();
+
+        let mut events: Vec<AppEvent> = workspace
+            .running_applications()
+            .into_iter()
+            .filter(|application| filter.matches_application(application))
+            .map(|application| AppEvent::AlreadyRunning {
+                hidden: application.hidden(),
+                finished_launching: application.is_finished_launching(),
+                app: application.into(),
+            })
+            .collect();
+
+        let frontmost: Id = msg_send![workspace.ptr(), frontmostApplication];
+
+        if let Some(frontmost) = RunningApplication::from_ptr(frontmost) {
+            if notification_types.contains(&NotificationType::Activated)
+                && filter.matches_application(&frontmost)
+            {
+                events.push(AppEvent::Activated {
+                    app: frontmost.into(),
+                });
+            }
+        }
+
+        pool.drain();
+
+        events
+    }
+}
+
+/// How a paused watcher treats the notifications it receives.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PauseMode {
+    /// Notifications received while paused are discarded.
+    Drop,
+    /// Notifications received while paused are delivered, in order, on resume.
+    Buffer,
+}
+
+type Sink = Box<dyn EventSink>;
+
+enum SinkEvent {
+    Event(AppEvent, EventStamp),
+    Error(AppWatcherError),
+}
+
+/// An Objective-C object that is only messaged through thread-safe APIs.
+struct SendId(Id);
+
+unsafe impl Send for SendId {}
+
+/// A run loop that is only stopped from other threads, which `CFRunLoopStop` allows.
+struct SendRunLoop(CFRunLoop);
+
+unsafe impl Send for SendRunLoop {}
+
+/// State shared between a started watcher's Objective-C object and its handles.
+struct WatcherState {
+    running: AtomicBool,
+    paused: AtomicBool,
+    pause_mode: Mutex<PauseMode>,
+    queue: Mutex<VecDeque<SinkEvent>>,
+    /// The next sequence number, only changed while `queue` is locked so numbers follow the
+    /// queue's order.
+    sequence: AtomicU64,
+    sink: Mutex<Option<Sink>>,
+    filter: AppFilter,
+    /// The notification types the watcher observes.
+    notification_types: Vec<NotificationType>,
+    /// The watcher object and the notification center it observes, until it's stopped.
+    registration: Mutex<Option<(SendId, SendId)>>,
+    /// Whether [`AppWatcherHandle::run`] is running the application's event loop.
+    runs_app: AtomicBool,
+    /// The run loop that [`AppWatcherHandle::run_headless`] is running.
+    run_loop: Mutex<Option<SendRunLoop>>,
+    /// The thread started by [`NSAppWatcher::spawn`].
+    thread: Mutex<Option<JoinHandle<()>>>,
+    stop_lock: Mutex<()>,
+    stopped: Condvar,
+}
+
+impl WatcherState {
+    fn new(sink: Sink, notification_types: &[NotificationType], filter: AppFilter) -> WatcherState {
+        WatcherState {
+            running: AtomicBool::new(true),
+            paused: AtomicBool::new(false),
+            pause_mode: Mutex::new(PauseMode::Drop),
+            queue: Mutex::new(VecDeque::new()),
+            sequence: AtomicU64::new(0),
+            sink: Mutex::new(Some(sink)),
+            filter,
+            notification_types: notification_types.to_vec(),
+            registration: Mutex::new(None),
+            runs_app: AtomicBool::new(false),
+            run_loop: Mutex::new(None),
+            thread: Mutex::new(None),
+            stop_lock: Mutex::new(()),
+            stopped: Condvar::new(),
+        }
+    }
+
+    fn push(&self, mut event: SinkEvent) {
+        if !self.running.load(Ordering::SeqCst) {
+            return;
+        }
+
+        {
+            let mut queue = lock(&self.queue);
+
+            // Dropped notifications use up a sequence number too, so consumers can tell.
+            if let SinkEvent::Event(_, stamp) = &mut event {
+                stamp.sequence = self.sequence.fetch_add(1, Ordering::SeqCst);
+            }
+
+            if self.paused.load(Ordering::SeqCst) && *lock(&self.pause_mode) == PauseMode::Drop {
+                return;
+            }
+
+            queue.push_back(event);
+        }
+
+        self.flush();
+    }
+
+    /// Delivers queued notifications and errors to the sink, and drops the sink once the watcher
+    /// is stopped. A sink whose receiver has disconnected stops the watcher.
+    ///
+    /// If the sink is already in use, further up this thread's stack (e.g. a sink that pauses or
+    /// stops the watcher) or on another thread, that call picks up the queued events.
+    fn flush(&self) {
+        loop {
+            {
+                let mut sink = match self.sink.try_lock() {
+                    Ok(sink) => sink,
+                    Err(TryLockError::Poisoned(error)) => error.into_inner(),
+                    Err(TryLockError::WouldBlock) => return,
+                };
+
+                loop {
+                    if !self.running.load(Ordering::SeqCst) {
+                        sink.take();
+                        lock(&self.queue).clear();
+                        return;
+                    }
+
+                    if self.paused.load(Ordering::SeqCst) {
+                        return;
+                    }
+
+                    let Some(event) = lock(&self.queue).pop_front() else {
+                        break;
+                    };
+
+                    let Some(sink) = sink.as_mut() else {
+                        continue;
+                    };
+
+                    match event {
+                        SinkEvent::Event(event, stamp) => {
+                            if sink.send_event(event, stamp).is_err() {
+                                self.stop();
+                            }
+                        }
+                        SinkEvent::Error(error) => sink.on_error(error),
+                    }
+                }
+            }
+
+            // Another thread may have queued a notification after the queue was found empty, but
+            // before the sink was unlocked.
+            if lock(&self.queue).is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// Puts `snapshot` at the front of the queue, dropping the queued notifications it already
+    /// accounts for: launches of applications it lists as launched (or launching), terminations
+    /// of applications that neither it nor a queued launch mentions, hiding and unhiding of
+    /// applications it lists, and, if it reports the frontmost application, activation and
+    /// deactivation of applications it lists.
+    ///
+    /// The queue is then renumbered from 0, since the snapshot comes first.
+    fn merge_snapshot(&self, snapshot: Vec<AppEvent>) {
+        let finished_launching: HashMap<usize, bool> = snapshot
+            .iter()
+            .filter_map(|event| match event {
+                AppEvent::AlreadyRunning {
+                    app,
+                    finished_launching,
+                    ..
+                } => Some((app.pid, *finished_launching)),
+                _ => None,
+            })
+            .collect();
+        let reports_frontmost = snapshot
+            .iter()
+            .any(|event| event.notification_type() == NotificationType::Activated);
+
+        let mut queue = lock(&self.queue);
+        let mut launched_since = HashSet::new();
+
+        queue.retain(|event| {
+            let SinkEvent::Event(event, _) = event else {
+                return true;
+            };
+            let pid = event.app().pid;
+
+            match event.notification_type() {
+                NotificationType::Launching if finished_launching.contains_key(&pid) => false,
+                NotificationType::Launched if finished_launching.get(&pid) == Some(&true) => false,
+                NotificationType::Launching | NotificationType::Launched => {
+                    launched_since.insert(pid);
+                    true
+                }
+                NotificationType::Terminated => {
+                    finished_launching.contains_key(&pid) || launched_since.contains(&pid)
+                }
+                // The snapshot's `hidden` flags and `Activated` event are the current state.
+                NotificationType::Hidden | NotificationType::Unhidden => {
+                    !finished_launching.contains_key(&pid)
+                }
+                NotificationType::Activated | NotificationType::Deactivated => {
+                    !(reports_frontmost && finished_launching.contains_key(&pid))
+                }
+                _ => true,
+            }
+        });
+
+        let stamp = EventStamp::now(0);
+
+        for event in snapshot.into_iter().rev() {
+            queue.push_front(SinkEvent::Event(event, stamp));
+        }
+
+        // Number the snapshot first, followed by the live notifications that were held back.
+        let mut sequence = 0;
+
+        for event in queue.iter_mut() {
+            if let SinkEvent::Event(_, stamp) = event {
+                stamp.sequence = sequence;
+                sequence += 1;
+            }
+        }
+
+        self.sequence.store(sequence, Ordering::SeqCst);
+    }
+
+    fn stop(&self) {
+        if !self.running.swap(false, Ordering::SeqCst) {
+            return;
+        }
+
+        if let Some((watcher, center)) = lock(&self.registration).take() {
+            unsafe {
+                let watcher = NSAppWatcher::from_ptr(watcher.0).unwrap();
+                let mut center = NotificationCenter::from_ptr(center.0).unwrap();
+
+                for notification_type in &self.notification_types {
+                    center.remove_observer(&watcher, *notification_type);
+                }
+
+                let _: () = msg_send![watcher.ptr(), release];
+                let _: () = msg_send![center.ptr(), release];
+            }
+        }
+
+        self.flush();
+
+        if self.runs_app.swap(false, Ordering::SeqCst) {
+            unsafe { stop_app() };
+        }
+
+        if let Some(run_loop) = lock(&self.run_loop).as_ref() {
+            run_loop.0.stop();
+        }
+
+        let _stop_lock = lock(&self.stop_lock);
+        self.stopped.notify_all();
+    }
+}
+
+/// Controls a started [`NSAppWatcher`].
+///
+/// Handles are cheap to clone, and every clone controls the same watcher. Dropping the handles
+/// doesn't stop the watcher.
+#[derive(Clone)]
+pub struct AppWatcherHandle {
+    state: Arc<WatcherState>,
+}
+
+impl AppWatcherHandle {
+    /// Stops the watcher for good.
+    ///
+    /// Its observers are unregistered and its sender is dropped, so receivers see the channel
+    /// disconnect. Buffered notifications are discarded. If [`run`](Self::run) is running the
+    /// application's event loop, it returns.
+    pub fn stop(&self) {
+        self.state.stop();
+    }
+
+    /// Pauses delivery. Notifications received until [`resume`](Self::resume) are dropped or
+    /// buffered, depending on `mode`.
+    pub fn pause(&self, mode: PauseMode) {
+        *lock(&self.state.pause_mode) = mode;
+        self.state.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resumes delivery, first sending any notifications buffered while paused.
+    pub fn resume(&self) {
+        self.state.paused.store(false, Ordering::SeqCst);
+        self.state.flush();
+    }
+
+    /// Returns `true` until the watcher is stopped.
+    pub fn is_running(&self) -> bool {
+        self.state.running.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if the watcher is running but paused.
+    pub fn is_paused(&self) -> bool {
+        self.is_running() && self.state.paused.load(Ordering::SeqCst)
+    }
+
+    /// Runs the application's event loop on the current thread, which must be the main thread,
+    /// until the watcher is stopped.
+    pub fn run(&self) {
+        self.state.runs_app.store(true, Ordering::SeqCst);
+
+        if self.is_running() {
+            unsafe { NSApp().run() };
+        }
+
+        self.state.runs_app.store(false, Ordering::SeqCst);
+    }
+
+    /// Runs the current thread's `CFRunLoop` until the watcher is stopped, without involving
+    /// `NSApplication`, so it can be used from any thread.
+    pub fn run_headless(&self) {
+        let run_loop = CFRunLoop::get_current();
+        *lock(&self.state.run_loop) = Some(SendRunLoop(run_loop.clone()));
+
+        // A run loop without any sources returns immediately, so give it a timer that never fires.
+        let timer =
+            CFRunLoopTimer::new(DISTANT_FUTURE, 0.0, 0, 0, keep_alive, std::ptr::null_mut());
+
+        unsafe {
+            run_loop.add_timer(&timer, kCFRunLoopDefaultMode);
+
+            // Time out now and then in case the watcher was stopped before the run loop started.
+            while self.is_running() {
+                CFRunLoop::run_in_mode(kCFRunLoopDefaultMode, Duration::from_secs(1), false);
+            }
+
+            run_loop.remove_timer(&timer, kCFRunLoopDefaultMode);
+        }
+
+        *lock(&self.state.run_loop) = None;
+    }
+
+    /// Blocks until the watcher is stopped and, if it was [`spawn`](NSAppWatcher::spawn)ed, its
+    /// thread has exited.
+    pub fn join(&self) {
+        let mut stop_lock = lock(&self.state.stop_lock);
+
+        while self.is_running() {
+            stop_lock = self
+                .state
+                .stopped
+                .wait(stop_lock)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        drop(stop_lock);
+
+        let thread = lock(&self.state.thread).take();
+
+        if let Some(thread) = thread {
+            if thread.thread().id() != std::thread::current().id() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
 #[derive(CocoaType)]
 pub struct NSAppWatcher {
     ptr: Id,
@@ -120,50 +625,135 @@ pub struct NSAppWatcher {
 
 #[allow(clippy::new_without_default)]
 impl NSAppWatcher {
-    unsafe fn set_sender<T>(&mut self, sender: SenderWrap<T>) {
-        let ivar = self
-            .ptr
-            .as_mut()
-            .expect("Could not get reference to AppWatcher")
-            .get_mut_ivar::<SenderWrap<T>>("channelSenderPtr");
+    fn state(&self) -> Option<&WatcherState> {
+        unsafe {
+            let object = self.ptr.as_ref()?;
+            let state = *object.get_ivar::<*mut c_void>(STATE_IVAR) as *const WatcherState;
 
-        *ivar = sender;
+            state.as_ref()
+        }
     }
 
-    unsafe fn get_sender<T>(&mut self) -> &mut SenderWrap<T> {
-        return self
-            .ptr
-            .as_mut()
-            .expect("Could not get reference to AppWatcher")
-            .get_mut_ivar::<SenderWrap<T>>("channelSenderPtr");
+    /// Starts observing the shared workspace's application notifications, delivering each one to
+    /// `sink`, e.g. a channel sender.
+    ///
+    /// This only registers the watcher. Notifications are delivered while the current thread's run
+    /// loop runs, whether that's the host application's event loop, [`AppWatcherHandle::run`] or
+    /// [`AppWatcherHandle::run_headless`].
+    pub fn start(sink: impl EventSink) -> AppWatcherHandle {
+        unsafe {
+            let shared_workspace = Workspace::shared_workspace();
+
+            let mut notification_center = shared_workspace.notification_center();
+
+            Self::start_on(&mut notification_center, sink)
+        }
+    }
+
+    /// Like [`start`](Self::start), but observes `notification_center` instead of the shared
+    /// workspace's center.
+    pub fn start_on(
+        notification_center: &mut NotificationCenter,
+        sink: impl EventSink,
+    ) -> AppWatcherHandle {
+        Self::start_with(
+            notification_center,
+            Box::new(sink),
+            AppWatcherBuilder::new(),
+        )
+    }
+
+    /// Returns a builder for a watcher that only observes some notification types or
+    /// applications.
+    pub fn builder() -> AppWatcherBuilder {
+        AppWatcherBuilder::new()
     }
 
-    fn run(&mut self) {
+    pub(crate) fn start_with(
+        notification_center: &mut NotificationCenter,
+        sink: Sink,
+        options: AppWatcherBuilder,
+    ) -> AppWatcherHandle {
+        ensure_declared();
+
+        let notification_types = options.selected_types();
+        let state = Arc::new(WatcherState::new(
+            sink,
+            notification_types,
+            options.filter.clone(),
+        ));
+
+        // Hold back live notifications until the snapshot is in the queue.
+        if options.snapshot {
+            *lock(&state.pause_mode) = PauseMode::Buffer;
+            state.paused.store(true, Ordering::SeqCst);
+        }
+
         unsafe {
-            let app = NSApp();
-            app.run();
-        };
+            let ptr: Id = msg_send![class!(NSAppWatcher), new];
+            let state_ptr = Arc::into_raw(state.clone()) as *mut c_void;
+            (*ptr).set_ivar::<*mut c_void>(STATE_IVAR, state_ptr);
+
+            let watcher = NSAppWatcher { ptr };
+
+            for notification_type in notification_types {
+                notification_center.add_observer(&watcher, *notification_type, None);
+            }
+
+            let center: Id = msg_send![notification_center.ptr(), retain];
+            *lock(&state.registration) = Some((SendId(ptr), SendId(center)));
+        }
+
+        let handle = AppWatcherHandle { state };
+
+        if options.snapshot {
+            handle.state.merge_snapshot(take_snapshot(
+                &handle.state.filter,
+                &handle.state.notification_types,
+            ));
+            handle.resume();
+        }
+
+        handle
     }
 
-    pub fn start_with_sender<T>(sender: Sender<T>)
+    /// Starts watching on a new thread, which runs its own run loop until the watcher is stopped.
+    ///
+    /// Unlike [`start_with_sender`](Self::start_with_sender), this doesn't take over the main
+    /// thread or `NSApp`, so it can be embedded in a process with its own event loop.
+    pub fn spawn(sink: impl EventSink) -> AppWatcherHandle {
+        Self::spawn_with(move || NSAppWatcher::start(sink))
+    }
+
+    /// Calls `start` on a new thread, then runs that thread's run loop until the watcher is
+    /// stopped.
+    pub(crate) fn spawn_with<F>(start: F) -> AppWatcherHandle
     where
-        T: From<AppNotification>,
+        F: FnOnce() -> AppWatcherHandle + Send + 'static,
     {
-        unsafe {
-            ensure_declared::<T>();
+        let (handle_sender, handle_receiver) = crossbeam::channel::bounded(1);
 
-            // get pointer to sender
-            let sender_ptr: *const Sender<T> = &sender;
-            let sender_wrap = SenderWrap(sender_ptr);
+        let thread = std::thread::Builder::new()
+            .name("app-watcher".to_string())
+            .spawn(move || {
+                let handle = start();
+                let _ = handle_sender.send(handle.clone());
 
-            let ptr: Id = msg_send![class!(NSAppWatcher), new];
-            let mut watcher = NSAppWatcher { ptr };
+                handle.run_headless();
+            })
+            .expect("failed to spawn the app watcher thread");
 
-            watcher.set_sender(sender_wrap);
+        let handle: AppWatcherHandle = handle_receiver
+            .recv()
+            .expect("the app watcher thread exited before starting");
+        *lock(&handle.state.thread) = Some(thread);
 
-            watcher.register_callbacks();
-            watcher.run();
-        }
+        handle
+    }
+
+    /// Starts watching, then runs the application's event loop until the watcher is stopped.
+    pub fn start_with_sender(sink: impl EventSink) {
+        Self::start(sink).run();
     }
 
     pub fn register_callbacks(&mut self) {
@@ -172,13 +762,15 @@ impl NSAppWatcher {
 
             let mut notification_center = shared_workspace.notification_center();
 
-            notification_center.add_observer(self, NotificationType::Activated, None);
-            notification_center.add_observer(self, NotificationType::Deactivated, None);
-            notification_center.add_observer(self, NotificationType::Hidden, None);
-            notification_center.add_observer(self, NotificationType::Unhidden, None);
-            notification_center.add_observer(self, NotificationType::Launched, None);
-            notification_center.add_observer(self, NotificationType::Launching, None);
-            notification_center.add_observer(self, NotificationType::Terminated, None);
+            self.register_callbacks_on(&mut notification_center);
+        }
+    }
+
+    /// Observes the workspace notifications on `notification_center` instead of the shared
+    /// workspace's center, e.g. a private center that a test posts synthetic notifications to.
+    pub fn register_callbacks_on(&mut self, notification_center: &mut NotificationCenter) {
+        for notification_type in NotificationType::ALL {
+            unsafe { notification_center.add_observer(self, notification_type, None) };
         }
     }
 
@@ -188,21 +780,28 @@ impl NSAppWatcher {
 
             let mut notification_center = shared_workspace.notification_center();
 
-            notification_center.remove_observer(self, NotificationType::Activated);
-            notification_center.remove_observer(self, NotificationType::Deactivated);
-            notification_center.remove_observer(self, NotificationType::Hidden);
-            notification_center.remove_observer(self, NotificationType::Unhidden);
-            notification_center.remove_observer(self, NotificationType::Launched);
-            notification_center.remove_observer(self, NotificationType::Launching);
-            notification_center.remove_observer(self, NotificationType::Terminated);
+            self.unregister_callbacks_from(&mut notification_center);
+        }
+    }
+
+    /// Stops observing the workspace notifications on `notification_center`.
+    pub fn unregister_callbacks_from(&mut self, notification_center: &mut NotificationCenter) {
+        for notification_type in NotificationType::ALL {
+            notification_center.remove_observer(self, notification_type);
         }
     }
 
+    /// Returns `true` if the watcher was started and hasn't been stopped.
     pub fn is_running(&self) -> bool {
-        false
+        self.state()
+            .is_some_and(|state| state.running.load(Ordering::SeqCst))
     }
 
+    /// Returns `true` if the watcher is running but paused.
     pub fn is_paused(&self) -> bool {
-        false
+        self.is_running()
+            && self
+                .state()
+                .is_some_and(|state| state.paused.load(Ordering::SeqCst))
     }
 }
@@ -0,0 +1,124 @@
+use crate::{
+    AppFilter, AppWatcherHandle, EventSink, NSAppWatcher, NotificationCenter, NotificationType,
+};
+
+/// Configures which notifications an [`NSAppWatcher`] observes before starting it.
+///
+/// Only the selected notification types are registered with the notification center, and
+/// notifications about applications that don't pass the [`AppFilter`] are dropped before they're
+/// parsed. Without any types, every type is observed.
+///
+/// ```no_run
+/// use cocoa_utils::{AppNotification, NSAppWatcher, NotificationType};
+///
+/// let (sender, receiver) = crossbeam::channel::unbounded::<AppNotification>();
+///
+/// let handle = NSAppWatcher::builder()
+///     .notification_types([NotificationType::Launched, NotificationType::Terminated])
+///     .bundle_identifier("com.apple.Safari")
+///     .spawn(sender);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AppWatcherBuilder {
+    notification_types: Vec<NotificationType>,
+    pub(crate) filter: AppFilter,
+    pub(crate) snapshot: bool,
+}
+
+impl AppWatcherBuilder {
+    pub fn new() -> AppWatcherBuilder {
+        AppWatcherBuilder::default()
+    }
+
+    /// Observes `notification_type`, in addition to any types selected before.
+    pub fn notification_type(mut self, notification_type: NotificationType) -> AppWatcherBuilder {
+        if !self.notification_types.contains(&notification_type) {
+            self.notification_types.push(notification_type);
+        }
+        self
+    }
+
+    /// Observes each of `notification_types`, in addition to any types selected before.
+    pub fn notification_types(
+        self,
+        notification_types: impl IntoIterator<Item = NotificationType>,
+    ) -> AppWatcherBuilder {
+        notification_types
+            .into_iter()
+            .fold(self, AppWatcherBuilder::notification_type)
+    }
+
+    /// Replaces the application filter.
+    pub fn filter(mut self, filter: AppFilter) -> AppWatcherBuilder {
+        self.filter = filter;
+        self
+    }
+
+    /// See [`AppFilter::bundle_identifier`].
+    pub fn bundle_identifier(mut self, bundle_identifier: impl Into<String>) -> AppWatcherBuilder {
+        self.filter = self.filter.bundle_identifier(bundle_identifier);
+        self
+    }
+
+    /// See [`AppFilter::pid`].
+    pub fn pid(mut self, pid: usize) -> AppWatcherBuilder {
+        self.filter = self.filter.pid(pid);
+        self
+    }
+
+    /// See [`AppFilter::name_pattern`].
+    pub fn name_pattern(mut self, pattern: impl Into<String>) -> AppWatcherBuilder {
+        self.filter = self.filter.name_pattern(pattern);
+        self
+    }
+
+    /// Whether to start with a snapshot of the applications that are already running.
+    ///
+    /// The sink first receives an [`AppEvent::AlreadyRunning`](crate::AppEvent::AlreadyRunning)
+    /// event for each running application that passes the filter, then an `Activated` event for
+    /// the frontmost one, before any live notification. Observers are registered before the
+    /// snapshot is taken, and notifications that the snapshot already accounts for are dropped,
+    /// so nothing is missed or reported twice.
+    ///
+    /// `AlreadyRunning` events are only ever sent for a snapshot, so enabling it selects them
+    /// whatever the notification types. The `Activated` event is only sent if `Activated` is
+    /// among the selected types.
+    pub fn snapshot(mut self, snapshot: bool) -> AppWatcherBuilder {
+        self.snapshot = snapshot;
+        self
+    }
+
+    pub(crate) fn selected_types(&self) -> &[NotificationType] {
+        if self.notification_types.is_empty() {
+            &NotificationType::ALL
+        } else {
+            &self.notification_types
+        }
+    }
+
+    /// Starts the watcher on the shared workspace's notification center, see
+    /// [`NSAppWatcher::start`].
+    pub fn start(self, sink: impl EventSink) -> AppWatcherHandle {
+        unsafe {
+            let shared_workspace = crate::Workspace::shared_workspace();
+
+            let mut notification_center = shared_workspace.notification_center();
+
+            self.start_on(&mut notification_center, sink)
+        }
+    }
+
+    /// Starts the watcher on `notification_center`, see [`NSAppWatcher::start_on`].
+    pub fn start_on(
+        self,
+        notification_center: &mut NotificationCenter,
+        sink: impl EventSink,
+    ) -> AppWatcherHandle {
+        NSAppWatcher::start_with(notification_center, Box::new(sink), self)
+    }
+
+    /// Starts the watcher on a new thread, see [`NSAppWatcher::spawn`].
+    pub fn spawn(self, sink: impl EventSink) -> AppWatcherHandle {
+        NSAppWatcher::spawn_with(move || self.start(sink))
+    }
+}
@@ -0,0 +1,25 @@
+use std::time::SystemTime;
+
+/// A source of the current time, so time-based consumers can be driven by a fake clock.
+pub trait Clock: Send + 'static {
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's wall clock.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> SystemTime + Send + 'static,
+{
+    fn now(&self) -> SystemTime {
+        self()
+    }
+}
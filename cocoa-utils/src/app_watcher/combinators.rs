@@ -0,0 +1,846 @@
+//! Adapters that clean up streams of [`AppNotification`]s.
+//!
+//! The adapters work on the time each notification was received: its
+//! [`stamp`](AppNotification::stamp) if it has one, or the adapter's [`Clock`] when it's pulled
+//! from the input otherwise. Since they're driven by their input, an adapter that holds a
+//! notification back (e.g. to see whether another one follows within a window) only releases it
+//! once the next notification arrives or the input ends. As streams, adapters can also be given a
+//! tick input with `with_ticks`, to release held notifications once their window has passed.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+
+use crate::{AppNotification, Clock, EventStamp, NotificationType, RunningAppInfo, SystemClock};
+
+/// A notification, or a focus change made of a `Deactivated` and an `Activated` notification,
+/// see [`AppNotificationIterExt::coalesce_focus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusEvent {
+    /// `to` became frontmost. `from` is the application that was deactivated just before, if
+    /// any.
+    FocusChanged {
+        from: Option<RunningAppInfo>,
+        to: RunningAppInfo,
+        /// The stamp of the `Activated` notification.
+        stamp: Option<EventStamp>,
+    },
+    /// Any other notification, passed through.
+    Other(AppNotification),
+}
+
+/// A notification, or a launch made of a `Launching` and a `Launched` notification, see
+/// [`AppNotificationIterExt::join_launches`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchEvent {
+    /// The application finished launching.
+    Launched {
+        app: RunningAppInfo,
+        /// The time between the `Launching` and `Launched` notifications, or `None` if the
+        /// `Launching` notification wasn't seen.
+        latency: Option<Duration>,
+        /// The stamp of the `Launched` notification.
+        stamp: Option<EventStamp>,
+    },
+    /// Any other notification, passed through.
+    Other(AppNotification),
+}
+
+/// The notification that was just received and when, or `None` at the end of the input.
+type Input = Option<(AppNotification, SystemTime)>;
+
+fn elapsed(from: SystemTime, to: SystemTime) -> Duration {
+    to.duration_since(from).unwrap_or_default()
+}
+
+/// The state of an adapter, which turns its input into output.
+trait AdapterState<T> {
+    fn step(&mut self, input: Input, output: &mut VecDeque<T>);
+
+    /// Releases what was held back for a window that has passed by `now`.
+    #[cfg(feature = "async")]
+    fn tick(&mut self, _now: SystemTime, _output: &mut VecDeque<T>) {}
+}
+
+#[derive(Debug)]
+struct DebounceState {
+    window: Duration,
+    /// The latest notification about each application, by pid.
+    latest: HashMap<usize, (AppNotification, SystemTime)>,
+}
+
+impl DebounceState {
+    /// Releases the notifications that have been quiet for the window by `now`, or all of them.
+    fn release(&mut self, now: Option<SystemTime>, output: &mut VecDeque<AppNotification>) {
+        let mut quiet: Vec<(usize, SystemTime)> = self
+            .latest
+            .iter()
+            .filter(|(_, (_, time))| match now {
+                Some(now) => elapsed(*time, now) >= self.window,
+                None => true,
+            })
+            .map(|(pid, (_, time))| (*pid, *time))
+            .collect();
+        quiet.sort_by_key(|(_, time)| *time);
+
+        for (pid, _) in quiet {
+            if let Some((notification, _)) = self.latest.remove(&pid) {
+                output.push_back(notification);
+            }
+        }
+    }
+}
+
+impl AdapterState<AppNotification> for DebounceState {
+    fn step(&mut self, input: Input, output: &mut VecDeque<AppNotification>) {
+        self.release(input.as_ref().map(|(_, time)| *time), output);
+
+        if let Some((notification, time)) = input {
+            self.latest
+                .insert(notification.app.pid, (notification, time));
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn tick(&mut self, now: SystemTime, output: &mut VecDeque<AppNotification>) {
+        self.release(Some(now), output);
+    }
+}
+
+#[derive(Debug)]
+struct CoalesceFocusState {
+    window: Duration,
+    /// A `Deactivated` notification that may be followed by an `Activated` one.
+    deactivated: Option<(AppNotification, SystemTime)>,
+}
+
+impl AdapterState<FocusEvent> for CoalesceFocusState {
+    fn step(&mut self, input: Input, output: &mut VecDeque<FocusEvent>) {
+        let Some((notification, time)) = input else {
+            output.extend(
+                self.deactivated
+                    .take()
+                    .map(|(notification, _)| FocusEvent::Other(notification)),
+            );
+            return;
+        };
+
+        let mut from = None;
+
+        if let Some((deactivated, deactivated_at)) = self.deactivated.take() {
+            if notification.notification_type == NotificationType::Activated
+                && elapsed(deactivated_at, time) <= self.window
+            {
+                from = Some(deactivated.app);
+            } else {
+                output.push_back(FocusEvent::Other(deactivated));
+            }
+        }
+
+        match notification.notification_type {
+            NotificationType::Activated => output.push_back(FocusEvent::FocusChanged {
+                from,
+                to: notification.app,
+                stamp: notification.stamp,
+            }),
+            NotificationType::Deactivated => self.deactivated = Some((notification, time)),
+            _ => output.push_back(FocusEvent::Other(notification)),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn tick(&mut self, now: SystemTime, output: &mut VecDeque<FocusEvent>) {
+        let expired = self
+            .deactivated
+            .as_ref()
+            .is_some_and(|(_, deactivated_at)| elapsed(*deactivated_at, now) > self.window);
+
+        if expired {
+            output.extend(
+                self.deactivated
+                    .take()
+                    .map(|(deactivated, _)| FocusEvent::Other(deactivated)),
+            );
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct JoinLaunchesState {
+    /// `Launching` notifications waiting for their `Launched` notification, by pid.
+    launching: HashMap<usize, (AppNotification, SystemTime)>,
+}
+
+impl AdapterState<LaunchEvent> for JoinLaunchesState {
+    fn step(&mut self, input: Input, output: &mut VecDeque<LaunchEvent>) {
+        let Some((notification, time)) = input else {
+            let mut launching: Vec<_> = self
+                .launching
+                .drain()
+                .map(|(_, launching)| launching)
+                .collect();
+            launching.sort_by_key(|(_, time)| *time);
+
+            output.extend(
+                launching
+                    .into_iter()
+                    .map(|(notification, _)| LaunchEvent::Other(notification)),
+            );
+            return;
+        };
+
+        let pid = notification.app.pid;
+
+        match notification.notification_type {
+            NotificationType::Launching => {
+                // A second `Launching` for the same pid replaces the first one.
+                if let Some((previous, _)) = self.launching.insert(pid, (notification, time)) {
+                    output.push_back(LaunchEvent::Other(previous));
+                }
+            }
+            NotificationType::Launched => {
+                let latency = self
+                    .launching
+                    .remove(&pid)
+                    .map(|(_, launching_at)| elapsed(launching_at, time));
+
+                output.push_back(LaunchEvent::Launched {
+                    app: notification.app,
+                    latency,
+                    stamp: notification.stamp,
+                });
+            }
+            NotificationType::Terminated => {
+                // The launch failed, so pass its `Launching` notification on as is.
+                if let Some((launching, _)) = self.launching.remove(&pid) {
+                    output.push_back(LaunchEvent::Other(launching));
+                }
+
+                output.push_back(LaunchEvent::Other(notification));
+            }
+            _ => output.push_back(LaunchEvent::Other(notification)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BatchState {
+    window: Duration,
+    batch: Vec<AppNotification>,
+    /// When the first notification of the batch was received.
+    started: Option<SystemTime>,
+}
+
+impl AdapterState<Vec<AppNotification>> for BatchState {
+    fn step(&mut self, input: Input, output: &mut VecDeque<Vec<AppNotification>>) {
+        let Some((notification, time)) = input else {
+            if !self.batch.is_empty() {
+                output.push_back(std::mem::take(&mut self.batch));
+            }
+            return;
+        };
+
+        if let Some(started) = self.started {
+            if elapsed(started, time) >= self.window {
+                output.push_back(std::mem::take(&mut self.batch));
+                self.started = None;
+            }
+        }
+
+        self.started.get_or_insert(time);
+        self.batch.push(notification);
+    }
+
+    #[cfg(feature = "async")]
+    fn tick(&mut self, now: SystemTime, output: &mut VecDeque<Vec<AppNotification>>) {
+        if let Some(started) = self.started {
+            if elapsed(started, now) >= self.window {
+                output.push_back(std::mem::take(&mut self.batch));
+                self.started = None;
+            }
+        }
+    }
+}
+
+/// Output that's ready, and whether the input has ended.
+#[derive(Debug)]
+struct Output<T> {
+    ready: VecDeque<T>,
+    done: bool,
+}
+
+impl<T> Default for Output<T> {
+    fn default() -> Self {
+        Output {
+            ready: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+fn received_at<C: Clock>(notification: &AppNotification, clock: &C) -> SystemTime {
+    match notification.stamp {
+        Some(stamp) => stamp.time,
+        None => clock.now(),
+    }
+}
+
+/// Pulls notifications from `input` into `state` until it produces something.
+fn next_output<I, C, T>(
+    input: &mut I,
+    clock: &C,
+    state: &mut impl AdapterState<T>,
+    output: &mut Output<T>,
+) -> Option<T>
+where
+    I: Iterator<Item = AppNotification>,
+    C: Clock,
+{
+    loop {
+        if let Some(item) = output.ready.pop_front() {
+            return Some(item);
+        }
+
+        if output.done {
+            return None;
+        }
+
+        match input.next() {
+            Some(notification) => {
+                let time = received_at(&notification, clock);
+                state.step(Some((notification, time)), &mut output.ready);
+            }
+            None => {
+                output.done = true;
+                state.step(None, &mut output.ready);
+            }
+        }
+    }
+}
+
+/// A stream whose items tell a stream adapter to release what it held back for too long.
+#[cfg(feature = "async")]
+struct Ticks(std::pin::Pin<Box<dyn futures::Stream<Item = ()> + Send>>);
+
+#[cfg(feature = "async")]
+impl std::fmt::Debug for Ticks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Ticks")
+    }
+}
+
+#[cfg(feature = "async")]
+fn poll_output<S, C, T>(
+    input: &mut S,
+    ticks: &mut Option<Ticks>,
+    cx: &mut std::task::Context<'_>,
+    clock: &C,
+    state: &mut impl AdapterState<T>,
+    output: &mut Output<T>,
+) -> std::task::Poll<Option<T>>
+where
+    S: futures::Stream<Item = AppNotification> + Unpin,
+    C: Clock,
+{
+    use futures::StreamExt;
+    use std::task::Poll;
+
+    loop {
+        if let Some(item) = output.ready.pop_front() {
+            return Poll::Ready(Some(item));
+        }
+
+        if output.done {
+            return Poll::Ready(None);
+        }
+
+        if let Some(Ticks(stream)) = ticks {
+            match stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(())) => {
+                    state.tick(clock.now(), &mut output.ready);
+                    continue;
+                }
+                Poll::Ready(None) => *ticks = None,
+                Poll::Pending => {}
+            }
+        }
+
+        match futures::ready!(input.poll_next_unpin(cx)) {
+            Some(notification) => {
+                let time = received_at(&notification, clock);
+                state.step(Some((notification, time)), &mut output.ready);
+            }
+            None => {
+                output.done = true;
+                state.step(None, &mut output.ready);
+            }
+        }
+    }
+}
+
+/// Defines an adapter struct, its `with_clock` and `with_ticks` methods, and its `Iterator` and
+/// `Stream` impls.
+macro_rules! adapter {
+    ($(#[$attr:meta])* $name:ident, $state:ty, $item:ty) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        pub struct $name<I, C = SystemClock> {
+            input: I,
+            clock: C,
+            state: $state,
+            output: Output<$item>,
+            #[cfg(feature = "async")]
+            ticks: Option<Ticks>,
+        }
+
+        impl<I> $name<I> {
+            fn new(input: I, state: $state) -> $name<I> {
+                $name {
+                    input,
+                    clock: SystemClock,
+                    state,
+                    output: Output::default(),
+                    #[cfg(feature = "async")]
+                    ticks: None,
+                }
+            }
+        }
+
+        impl<I, C: Clock> $name<I, C> {
+            /// Reads the time of notifications without a stamp from `clock` instead of the system
+            /// clock.
+            pub fn with_clock<C2: Clock>(self, clock: C2) -> $name<I, C2> {
+                $name {
+                    input: self.input,
+                    clock,
+                    state: self.state,
+                    output: self.output,
+                    #[cfg(feature = "async")]
+                    ticks: self.ticks,
+                }
+            }
+
+            /// Each time `ticks` yields, e.g. on every tick of an interval timer, releases what
+            /// is held back for a window that has passed by the clock's time, instead of waiting
+            /// for the next notification.
+            ///
+            /// Ticks only apply when the adapter is used as a `Stream`.
+            #[cfg(feature = "async")]
+            pub fn with_ticks<T>(mut self, ticks: T) -> $name<I, C>
+            where
+                T: futures::Stream + Send + 'static,
+            {
+                use futures::StreamExt;
+
+                self.ticks = Some(Ticks(Box::pin(ticks.map(|_| ()))));
+                self
+            }
+        }
+
+        impl<I, C> Iterator for $name<I, C>
+        where
+            I: Iterator<Item = AppNotification>,
+            C: Clock,
+        {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<$item> {
+                next_output(&mut self.input, &self.clock, &mut self.state, &mut self.output)
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<S, C> futures::Stream for $name<S, C>
+        where
+            S: futures::Stream<Item = AppNotification> + Unpin,
+            C: Clock + Unpin,
+        {
+            type Item = $item;
+
+            fn poll_next(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<$item>> {
+                let this = self.get_mut();
+                poll_output(
+                    &mut this.input,
+                    &mut this.ticks,
+                    cx,
+                    &this.clock,
+                    &mut this.state,
+                    &mut this.output,
+                )
+            }
+        }
+    };
+}
+
+adapter!(
+    /// See [`AppNotificationIterExt::debounce_by_app`].
+    DebounceByApp,
+    DebounceState,
+    AppNotification
+);
+
+adapter!(
+    /// See [`AppNotificationIterExt::coalesce_focus`].
+    CoalesceFocus,
+    CoalesceFocusState,
+    FocusEvent
+);
+
+adapter!(
+    /// See [`AppNotificationIterExt::join_launches`].
+    JoinLaunches,
+    JoinLaunchesState,
+    LaunchEvent
+);
+
+adapter!(
+    /// See [`AppNotificationIterExt::batch_window`].
+    BatchWindow,
+    BatchState,
+    Vec<AppNotification>
+);
+
+fn debounce_by_app<I>(input: I, window: Duration) -> DebounceByApp<I> {
+    DebounceByApp::new(
+        input,
+        DebounceState {
+            window,
+            latest: HashMap::new(),
+        },
+    )
+}
+
+fn coalesce_focus<I>(input: I, window: Duration) -> CoalesceFocus<I> {
+    CoalesceFocus::new(
+        input,
+        CoalesceFocusState {
+            window,
+            deactivated: None,
+        },
+    )
+}
+
+fn join_launches<I>(input: I) -> JoinLaunches<I> {
+    JoinLaunches::new(input, JoinLaunchesState::default())
+}
+
+fn batch_window<I>(input: I, window: Duration) -> BatchWindow<I> {
+    BatchWindow::new(
+        input,
+        BatchState {
+            window,
+            batch: Vec::new(),
+            started: None,
+        },
+    )
+}
+
+/// Combinators for iterators of [`AppNotification`]s, such as a channel receiver's.
+///
+/// ```
+/// use std::time::Duration;
+/// use cocoa_utils::{AppNotificationIterExt, FocusEvent};
+///
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// # drop(sender);
+///
+/// for event in receiver.into_iter().coalesce_focus(Duration::from_millis(100)) {
+///     if let FocusEvent::FocusChanged { from, to, .. } = event {
+///         println!("{:?} -> {:?}", from.and_then(|app| app.localized_name), to.localized_name);
+///     }
+/// }
+/// ```
+pub trait AppNotificationIterExt: Iterator<Item = AppNotification> + Sized {
+    /// Only passes on the last notification about each application (by pid) in a burst: a
+    /// notification is held back until `window` passes without another one about the same
+    /// application.
+    fn debounce_by_app(self, window: Duration) -> DebounceByApp<Self> {
+        debounce_by_app(self, window)
+    }
+
+    /// Turns a `Deactivated` notification followed within `window` by an `Activated` one into a
+    /// single [`FocusEvent::FocusChanged`], and a lone `Activated` notification into one without
+    /// a `from`.
+    fn coalesce_focus(self, window: Duration) -> CoalesceFocus<Self> {
+        coalesce_focus(self, window)
+    }
+
+    /// Turns each `Launched` notification into a [`LaunchEvent::Launched`] with the latency since
+    /// the application's `Launching` notification, which is swallowed. `Launching` notifications
+    /// of launches that fail, or are still pending when the input ends, are passed on.
+    fn join_launches(self) -> JoinLaunches<Self> {
+        join_launches(self)
+    }
+
+    /// Groups notifications into batches that span less than `window` from their first
+    /// notification.
+    fn batch_window(self, window: Duration) -> BatchWindow<Self> {
+        batch_window(self, window)
+    }
+}
+
+impl<I: Iterator<Item = AppNotification>> AppNotificationIterExt for I {}
+
+/// The combinators of [`AppNotificationIterExt`], for streams such as
+/// `AppNotificationStream`.
+///
+/// An adapter that holds a notification back only looks at the time when it's polled for the
+/// next notification, so a notification held for a window is released once another one arrives,
+/// not when the window passes. To release it on time, give the adapter a tick input with its
+/// `with_ticks` method, such as tokio's `IntervalStream` for an interval shorter than the window.
+#[cfg(feature = "async")]
+pub trait AppNotificationStreamExt:
+    futures::Stream<Item = AppNotification> + Unpin + Sized
+{
+    /// See [`AppNotificationIterExt::debounce_by_app`].
+    fn debounce_by_app(self, window: Duration) -> DebounceByApp<Self> {
+        debounce_by_app(self, window)
+    }
+
+    /// See [`AppNotificationIterExt::coalesce_focus`].
+    fn coalesce_focus(self, window: Duration) -> CoalesceFocus<Self> {
+        coalesce_focus(self, window)
+    }
+
+    /// See [`AppNotificationIterExt::join_launches`].
+    fn join_launches(self) -> JoinLaunches<Self> {
+        join_launches(self)
+    }
+
+    /// See [`AppNotificationIterExt::batch_window`].
+    fn batch_window(self, window: Duration) -> BatchWindow<Self> {
+        batch_window(self, window)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: futures::Stream<Item = AppNotification> + Unpin> AppNotificationStreamExt for S {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+    use NotificationType::*;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn app(pid: usize) -> RunningAppInfo {
+        RunningAppInfo {
+            pid,
+            localized_name: None,
+            bundle_identifier: None,
+            bundle_url: None,
+        }
+    }
+
+    /// A notification stamped `seconds` after the epoch.
+    fn stamped(notification_type: NotificationType, pid: usize, seconds: u64) -> AppNotification {
+        AppNotification {
+            notification_type,
+            app: app(pid),
+            stamp: Some(EventStamp {
+                sequence: seconds,
+                time: at(seconds),
+                monotonic: Duration::from_secs(seconds),
+            }),
+        }
+    }
+
+    fn unstamped(notification_type: NotificationType, pid: usize) -> AppNotification {
+        AppNotification {
+            notification_type,
+            app: app(pid),
+            stamp: None,
+        }
+    }
+
+    /// A clock that the test sets, in seconds after the epoch.
+    fn fake_clock() -> (Arc<AtomicU64>, impl Clock + Unpin) {
+        let seconds = Arc::new(AtomicU64::new(0));
+        let clock = {
+            let seconds = seconds.clone();
+            move || at(seconds.load(Ordering::SeqCst))
+        };
+
+        (seconds, clock)
+    }
+
+    fn seconds(notification: &AppNotification) -> u64 {
+        let time = notification.stamp.unwrap().time;
+        time.duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn debounce_keeps_last_of_burst() {
+        let window = Duration::from_secs(10);
+        let input = vec![
+            stamped(Hidden, 1, 0),
+            stamped(Unhidden, 1, 9),
+            stamped(Hidden, 2, 12),
+            // Exactly one window after the last one about pid 1, so that one is released.
+            stamped(Hidden, 1, 19),
+            stamped(Unhidden, 1, 25),
+        ];
+
+        let output: Vec<(usize, u64)> = input
+            .into_iter()
+            .debounce_by_app(window)
+            .map(|notification| (notification.app.pid, seconds(&notification)))
+            .collect();
+
+        // Pending notifications are flushed in the order they were received at the end.
+        assert_eq!(output, [(1, 9), (2, 12), (1, 25)]);
+    }
+
+    #[test]
+    fn debounce_reads_clock_for_unstamped() {
+        let (now, clock) = fake_clock();
+        let mut input = vec![unstamped(Hidden, 1), unstamped(Unhidden, 1)].into_iter();
+
+        // The clock moves on each time a notification is pulled.
+        let mut debounced = std::iter::from_fn(|| {
+            now.fetch_add(10, Ordering::SeqCst);
+            input.next()
+        })
+        .debounce_by_app(Duration::from_secs(10))
+        .with_clock(clock);
+
+        assert_eq!(debounced.next().unwrap().notification_type, Hidden);
+        assert_eq!(debounced.next().unwrap().notification_type, Unhidden);
+        assert!(debounced.next().is_none());
+    }
+
+    #[test]
+    fn coalesce_focus_within_window() {
+        let window = Duration::from_secs(10);
+        let input = vec![
+            stamped(Deactivated, 1, 0),
+            // Exactly at the window's end still counts.
+            stamped(Activated, 2, 10),
+            stamped(Deactivated, 2, 20),
+            stamped(Activated, 3, 31),
+            stamped(Deactivated, 3, 40),
+        ];
+
+        let output: Vec<FocusEvent> = input.into_iter().coalesce_focus(window).collect();
+
+        assert_eq!(
+            output,
+            [
+                FocusEvent::FocusChanged {
+                    from: Some(app(1)),
+                    to: app(2),
+                    stamp: stamped(Activated, 2, 10).stamp,
+                },
+                FocusEvent::Other(stamped(Deactivated, 2, 20)),
+                FocusEvent::FocusChanged {
+                    from: None,
+                    to: app(3),
+                    stamp: stamped(Activated, 3, 31).stamp,
+                },
+                // Flushed at the end of the input.
+                FocusEvent::Other(stamped(Deactivated, 3, 40)),
+            ]
+        );
+    }
+
+    #[test]
+    fn join_launches_measures_latency() {
+        let input = vec![
+            stamped(Launching, 1, 0),
+            stamped(Launching, 2, 1),
+            stamped(Launched, 1, 4),
+            stamped(Launched, 3, 5),
+            stamped(Terminated, 2, 6),
+            stamped(Launching, 4, 7),
+        ];
+
+        let output: Vec<LaunchEvent> = input.into_iter().join_launches().collect();
+
+        assert_eq!(
+            output,
+            [
+                LaunchEvent::Launched {
+                    app: app(1),
+                    latency: Some(Duration::from_secs(4)),
+                    stamp: stamped(Launched, 1, 4).stamp,
+                },
+                LaunchEvent::Launched {
+                    app: app(3),
+                    latency: None,
+                    stamp: stamped(Launched, 3, 5).stamp,
+                },
+                LaunchEvent::Other(stamped(Launching, 2, 1)),
+                LaunchEvent::Other(stamped(Terminated, 2, 6)),
+                LaunchEvent::Other(stamped(Launching, 4, 7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_window_splits_at_window() {
+        let input = vec![
+            stamped(Launched, 1, 0),
+            stamped(Launched, 2, 9),
+            stamped(Launched, 3, 10),
+            stamped(Launched, 4, 15),
+        ];
+
+        let batches: Vec<Vec<u64>> = input
+            .into_iter()
+            .batch_window(Duration::from_secs(10))
+            .map(|batch| batch.iter().map(seconds).collect())
+            .collect();
+
+        assert_eq!(batches, [vec![0, 9], vec![10, 15]]);
+        assert_eq!(
+            std::iter::empty()
+                .batch_window(Duration::from_secs(10))
+                .count(),
+            0
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn ticks_release_held_notifications() {
+        use futures::channel::mpsc;
+        use futures::{FutureExt, StreamExt};
+
+        let (now, clock) = fake_clock();
+        let (notifications, input) = mpsc::unbounded();
+        let (tick, ticks) = mpsc::unbounded::<()>();
+
+        let mut debounced = input
+            .debounce_by_app(Duration::from_secs(10))
+            .with_clock(clock)
+            .with_ticks(ticks);
+
+        notifications.unbounded_send(unstamped(Hidden, 1)).unwrap();
+        assert_eq!(debounced.next().now_or_never(), None);
+
+        // A tick before the window has passed releases nothing.
+        now.store(9, Ordering::SeqCst);
+        tick.unbounded_send(()).unwrap();
+        assert_eq!(debounced.next().now_or_never(), None);
+
+        now.store(10, Ordering::SeqCst);
+        tick.unbounded_send(()).unwrap();
+        assert_eq!(
+            debounced.next().now_or_never(),
+            Some(Some(unstamped(Hidden, 1)))
+        );
+
+        // The stream still ends with its input once the ticks have ended.
+        drop(tick);
+        drop(notifications);
+        assert_eq!(debounced.next().now_or_never(), Some(None));
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use crate::{CocoaError, NotificationType};
+
+/// A failure inside a watcher's notification callbacks, reported through
+/// [`EventSink::on_error`](crate::EventSink::on_error) instead of unwinding into Objective-C.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppWatcherError {
+    /// A workspace notification couldn't be turned into an [`AppNotification`](crate::AppNotification),
+    /// e.g. because its `userInfo` has no application.
+    Parse {
+        notification_type: NotificationType,
+        error: CocoaError,
+    },
+    /// A callback panicked, most likely in the sink. The notification being delivered is lost.
+    Panic { message: String },
+}
+
+impl fmt::Display for AppWatcherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppWatcherError::Parse {
+                notification_type,
+                error,
+            } => write!(
+                f,
+                "failed to parse {notification_type:?} notification: {error}"
+            ),
+            AppWatcherError::Panic { message } => {
+                write!(f, "watcher callback panicked: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppWatcherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppWatcherError::Parse { error, .. } => Some(error),
+            AppWatcherError::Panic { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use std::fmt;
+
+use crate::{AppEvent, AppNotification, AppWatcherError, EventStamp};
+
+/// The receiving end of an [`EventSink`] has gone away.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Disconnected;
+
+impl fmt::Display for Disconnected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the event sink's receiver has disconnected")
+    }
+}
+
+impl std::error::Error for Disconnected {}
+
+/// Where a watcher delivers the notifications it receives.
+///
+/// The watcher owns its sink and drops it when it's stopped, which is how channel receivers
+/// learn that no more notifications are coming, and stops itself as soon as `send` reports that
+/// the receiver has disconnected. `send` is called on the thread whose run loop delivers the
+/// notifications.
+///
+/// Implemented for the sending halves of crossbeam, `std::sync::mpsc` and (with the `tokio`
+/// feature) tokio channels, and for closures.
+pub trait EventSink: Send + 'static {
+    /// Delivers a notification, or reports that nobody is listening anymore.
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected>;
+
+    /// Delivers a notification with its type-specific payload and the watcher's stamp. This is
+    /// what the watcher calls; the default implementation flattens the event and passes it to
+    /// [`send`](Self::send).
+    fn send_event(&mut self, event: AppEvent, stamp: EventStamp) -> Result<(), Disconnected> {
+        let mut notification = AppNotification::from(event);
+        notification.stamp = Some(stamp);
+        self.send(notification)
+    }
+
+    /// Reports a notification that couldn't be delivered, or a panic caught in a callback. The
+    /// default implementation ignores the error.
+    fn on_error(&mut self, error: AppWatcherError) {
+        let _ = error;
+    }
+
+    /// Wraps the sink so errors are passed to `handler` instead of [`on_error`](Self::on_error).
+    fn with_error_handler<F>(self, handler: F) -> WithErrorHandler<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(AppWatcherError) + Send + 'static,
+    {
+        WithErrorHandler {
+            sink: self,
+            handler,
+        }
+    }
+}
+
+impl EventSink for Box<dyn EventSink> {
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        (**self).send(notification)
+    }
+
+    fn send_event(&mut self, event: AppEvent, stamp: EventStamp) -> Result<(), Disconnected> {
+        (**self).send_event(event, stamp)
+    }
+
+    fn on_error(&mut self, error: AppWatcherError) {
+        (**self).on_error(error)
+    }
+}
+
+/// A sink that passes errors to a closure, see [`EventSink::with_error_handler`].
+pub struct WithErrorHandler<S, F> {
+    sink: S,
+    handler: F,
+}
+
+impl<S, F> EventSink for WithErrorHandler<S, F>
+where
+    S: EventSink,
+    F: FnMut(AppWatcherError) + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        self.sink.send(notification)
+    }
+
+    fn send_event(&mut self, event: AppEvent, stamp: EventStamp) -> Result<(), Disconnected> {
+        self.sink.send_event(event, stamp)
+    }
+
+    fn on_error(&mut self, error: AppWatcherError) {
+        (self.handler)(error)
+    }
+}
+
+impl<F> EventSink for F
+where
+    F: FnMut(AppNotification) + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        self(notification);
+        Ok(())
+    }
+}
+
+/// A sink that passes [`AppEvent`]s, with their type-specific payloads, to a closure along with
+/// their stamps.
+///
+/// The closure reports [`Disconnected`] to stop the watcher, e.g. when forwarding events over a
+/// channel:
+///
+/// ```
+/// use cocoa_utils::{AppEvent, Disconnected, EventFn, EventSink};
+///
+/// # fn start(_sink: impl EventSink) {}
+/// let (sender, receiver) = std::sync::mpsc::channel::<AppEvent>();
+/// start(EventFn(move |event, _stamp| {
+///     sender.send(event).map_err(|_| Disconnected)
+/// }));
+/// # drop(receiver);
+/// ```
+pub struct EventFn<F>(pub F);
+
+impl<F> EventSink for EventFn<F>
+where
+    F: FnMut(AppEvent, Option<EventStamp>) -> Result<(), Disconnected> + Send + 'static,
+{
+    /// Passes the notification on as an event without any type-specific payload.
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        let event = AppEvent::from_parts(notification.notification_type, notification.app);
+        (self.0)(event, notification.stamp)
+    }
+
+    fn send_event(&mut self, event: AppEvent, stamp: EventStamp) -> Result<(), Disconnected> {
+        (self.0)(event, Some(stamp))
+    }
+}
+
+/// Sends each notification converted with `T::from`.
+impl<T> EventSink for crossbeam::channel::Sender<T>
+where
+    T: From<AppNotification> + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        crossbeam::channel::Sender::send(self, T::from(notification)).map_err(|_| Disconnected)
+    }
+}
+
+impl<T> EventSink for std::sync::mpsc::Sender<T>
+where
+    T: From<AppNotification> + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        std::sync::mpsc::Sender::send(self, T::from(notification)).map_err(|_| Disconnected)
+    }
+}
+
+/// Blocks the watcher while the channel is full.
+impl<T> EventSink for std::sync::mpsc::SyncSender<T>
+where
+    T: From<AppNotification> + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        std::sync::mpsc::SyncSender::send(self, T::from(notification)).map_err(|_| Disconnected)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T> EventSink for tokio::sync::mpsc::UnboundedSender<T>
+where
+    T: From<AppNotification> + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        tokio::sync::mpsc::UnboundedSender::send(self, T::from(notification))
+            .map_err(|_| Disconnected)
+    }
+}
+
+/// Blocks the watcher while the channel is full, so the watcher must not run on a thread that is
+/// driving a tokio runtime.
+#[cfg(feature = "tokio")]
+impl<T> EventSink for tokio::sync::mpsc::Sender<T>
+where
+    T: From<AppNotification> + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        self.blocking_send(T::from(notification))
+            .map_err(|_| Disconnected)
+    }
+}
+
+/// Blocks the watcher while the channel is full.
+#[cfg(feature = "async")]
+impl<T> EventSink for futures::channel::mpsc::Sender<T>
+where
+    T: From<AppNotification> + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        futures::executor::block_on(futures::SinkExt::send(self, T::from(notification)))
+            .map_err(|_| Disconnected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NotificationType, RunningAppInfo};
+
+    fn app(pid: usize) -> RunningAppInfo {
+        RunningAppInfo {
+            pid,
+            localized_name: None,
+            bundle_identifier: Some(format!("com.example.app{pid}")),
+            bundle_url: None,
+        }
+    }
+
+    fn notification(pid: usize) -> AppNotification {
+        AppNotification {
+            notification_type: NotificationType::Launched,
+            app: app(pid),
+            stamp: None,
+        }
+    }
+
+    /// A channel item built from a notification, to check that sinks convert with `T::from`.
+    #[derive(Debug, PartialEq)]
+    struct BundleId(Option<String>);
+
+    impl From<AppNotification> for BundleId {
+        fn from(notification: AppNotification) -> Self {
+            BundleId(notification.app.bundle_identifier)
+        }
+    }
+
+    #[test]
+    fn crossbeam_sender_converts_and_disconnects() {
+        let (mut sender, receiver) = crossbeam::channel::unbounded::<BundleId>();
+
+        assert_eq!(EventSink::send(&mut sender, notification(1)), Ok(()));
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            BundleId(Some("com.example.app1".to_string()))
+        );
+
+        drop(receiver);
+        assert_eq!(
+            EventSink::send(&mut sender, notification(2)),
+            Err(Disconnected)
+        );
+    }
+
+    #[test]
+    fn std_sender_stamps_events_and_disconnects() {
+        let (mut sender, receiver) = std::sync::mpsc::channel::<AppNotification>();
+        let stamp = EventStamp::now(3);
+        let event = AppEvent::from_parts(NotificationType::Hidden, app(1));
+
+        assert_eq!(sender.send_event(event, stamp), Ok(()));
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            AppNotification {
+                notification_type: NotificationType::Hidden,
+                app: app(1),
+                stamp: Some(stamp),
+            }
+        );
+
+        drop(receiver);
+        assert_eq!(
+            EventSink::send(&mut sender, notification(2)),
+            Err(Disconnected)
+        );
+    }
+
+    #[test]
+    fn sync_sender_disconnects() {
+        let (mut sender, receiver) = std::sync::mpsc::sync_channel::<BundleId>(1);
+
+        assert_eq!(EventSink::send(&mut sender, notification(1)), Ok(()));
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            BundleId(Some("com.example.app1".to_string()))
+        );
+
+        drop(receiver);
+        assert_eq!(
+            EventSink::send(&mut sender, notification(2)),
+            Err(Disconnected)
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_unbounded_sender_converts_and_disconnects() {
+        let (mut sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<BundleId>();
+
+        assert_eq!(EventSink::send(&mut sender, notification(1)), Ok(()));
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            BundleId(Some("com.example.app1".to_string()))
+        );
+
+        drop(receiver);
+        assert_eq!(
+            EventSink::send(&mut sender, notification(2)),
+            Err(Disconnected)
+        );
+    }
+
+    #[test]
+    fn closure_receives_notifications() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut sink = move |notification: AppNotification| sender.send(notification).unwrap();
+
+        assert_eq!(sink.send(notification(1)), Ok(()));
+        assert_eq!(receiver.try_recv().unwrap(), notification(1));
+    }
+
+    #[test]
+    fn event_fn_reports_disconnects() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut sink = EventFn(move |event: AppEvent, stamp: Option<EventStamp>| {
+            sender.send((event, stamp)).map_err(|_| Disconnected)
+        });
+
+        assert_eq!(sink.send(notification(1)), Ok(()));
+        let (event, stamp) = receiver.try_recv().unwrap();
+        assert_eq!(
+            event,
+            AppEvent::from_parts(NotificationType::Launched, app(1))
+        );
+        assert_eq!(stamp, None);
+
+        drop(receiver);
+        assert_eq!(sink.send(notification(2)), Err(Disconnected));
+    }
+
+    #[test]
+    fn error_handler_receives_errors() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut sink = (|_: AppNotification| {}).with_error_handler(move |error| {
+            sender.send(error.to_string()).unwrap();
+        });
+
+        sink.on_error(AppWatcherError::Panic {
+            message: "boom".to_string(),
+        });
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            "watcher callback panicked: boom"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::RunningAppInfo;
+
+#[cfg(target_os = "macos")]
+use crate::RunningApplication;
+
+/// Selects the applications a watcher reports on.
+///
+/// An application passes the filter if it matches any of the bundle identifiers, process
+/// identifiers or name patterns added to it. An empty filter lets every application through.
+///
+/// Name patterns are matched against the application's localized name, ignoring case. `*` matches
+/// any run of characters and `?` matches a single character.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppFilter {
+    bundle_identifiers: Vec<String>,
+    pids: Vec<usize>,
+    name_patterns: Vec<String>,
+}
+
+impl AppFilter {
+    /// Creates an empty filter, which lets every application through.
+    pub fn new() -> AppFilter {
+        AppFilter::default()
+    }
+
+    /// Lets through the application with the given bundle identifier, e.g. `com.apple.Safari`.
+    pub fn bundle_identifier(mut self, bundle_identifier: impl Into<String>) -> AppFilter {
+        self.bundle_identifiers.push(bundle_identifier.into());
+        self
+    }
+
+    /// Lets through the process with the given identifier.
+    pub fn pid(mut self, pid: usize) -> AppFilter {
+        self.pids.push(pid);
+        self
+    }
+
+    /// Lets through applications whose localized name matches `pattern`.
+    pub fn name_pattern(mut self, pattern: impl Into<String>) -> AppFilter {
+        self.name_patterns.push(pattern.into().to_lowercase());
+        self
+    }
+
+    /// Returns `true` if the filter lets every application through.
+    pub fn is_empty(&self) -> bool {
+        self.bundle_identifiers.is_empty() && self.pids.is_empty() && self.name_patterns.is_empty()
+    }
+
+    /// Returns `true` if an application with these properties passes the filter.
+    pub fn matches(
+        &self,
+        pid: usize,
+        bundle_identifier: Option<&str>,
+        localized_name: Option<&str>,
+    ) -> bool {
+        self.is_empty()
+            || self.matches_pid(pid)
+            || self.matches_bundle_identifier(bundle_identifier)
+            || self.matches_name(localized_name)
+    }
+
+    /// Returns `true` if `app` passes the filter.
+    pub fn matches_app(&self, app: &RunningAppInfo) -> bool {
+        self.matches(
+            app.pid,
+            app.bundle_identifier.as_deref(),
+            app.localized_name.as_deref(),
+        )
+    }
+
+    /// Returns `true` if `application` passes the filter, only querying the properties it needs.
+    #[cfg(target_os = "macos")]
+    pub fn matches_application(&self, application: &RunningApplication) -> bool {
+        unsafe {
+            self.is_empty()
+                || self.matches_pid(application.process_identifier())
+                || (!self.bundle_identifiers.is_empty()
+                    && self.matches_bundle_identifier(application.bundle_identifier().as_deref()))
+                || (!self.name_patterns.is_empty()
+                    && self.matches_name(application.localized_name().as_deref()))
+        }
+    }
+
+    fn matches_pid(&self, pid: usize) -> bool {
+        self.pids.contains(&pid)
+    }
+
+    fn matches_bundle_identifier(&self, bundle_identifier: Option<&str>) -> bool {
+        bundle_identifier.is_some_and(|bundle_identifier| {
+            self.bundle_identifiers
+                .iter()
+                .any(|candidate| candidate == bundle_identifier)
+        })
+    }
+
+    fn matches_name(&self, localized_name: Option<&str>) -> bool {
+        localized_name.is_some_and(|localized_name| {
+            let localized_name: Vec<char> = localized_name.to_lowercase().chars().collect();
+
+            self.name_patterns.iter().any(|pattern| {
+                let pattern: Vec<char> = pattern.chars().collect();
+                glob_match(&pattern, &localized_name)
+            })
+        })
+    }
+}
+
+/// Matches `text` against a pattern of literal characters, `*` and `?`.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` in the pattern, and of the text it was tried against.
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character.
+                Some((star, star_t)) => {
+                    backtrack = Some((star, star_t + 1));
+                    p = star + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::time::{Duration, SystemTime};
+
+use crate::{AppEvent, AppNotification, Clock, NotificationType, RunningAppInfo, SystemClock};
+
+/// Identifies an application across launches: by bundle identifier, or by pid for applications
+/// without one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AppKey {
+    BundleIdentifier(String),
+    Pid(usize),
+}
+
+impl From<&RunningAppInfo> for AppKey {
+    fn from(app: &RunningAppInfo) -> Self {
+        match &app.bundle_identifier {
+            Some(bundle_identifier) => AppKey::BundleIdentifier(bundle_identifier.clone()),
+            None => AppKey::Pid(app.pid),
+        }
+    }
+}
+
+impl fmt::Display for AppKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppKey::BundleIdentifier(bundle_identifier) => f.write_str(bundle_identifier),
+            AppKey::Pid(pid) => write!(f, "pid {pid}"),
+        }
+    }
+}
+
+/// A span of time during which an application was frontmost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FocusSession {
+    pub app: AppKey,
+    pub localized_name: Option<String>,
+    pub start: SystemTime,
+    pub end: SystemTime,
+}
+
+impl FocusSession {
+    pub fn duration(&self) -> Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+
+    /// The part of the session that falls within `range`.
+    pub fn overlap(&self, range: &Range<SystemTime>) -> Duration {
+        let start = self.start.max(range.start);
+        let end = self.end.min(range.end);
+
+        end.duration_since(start).unwrap_or_default()
+    }
+}
+
+/// Measures how long each application is frontmost, from `Activated`, `Deactivated` and
+/// `Terminated` notifications.
+///
+/// The clock is only used for the ongoing session and for notifications without a stamp.
+///
+/// An application stays frontmost until it's deactivated, another application is activated
+/// (so a missing `Deactivated` notification doesn't matter), or it terminates. Notifications
+/// about other applications than the frontmost one, such as a late `Deactivated`, are ignored.
+/// Sessions are keyed by [`AppKey`], but only the instance that is frontmost ends its session:
+/// when another instance of the same application is activated, the session carries on with it.
+/// A notification timestamped before an earlier one is treated as happening at the same time as
+/// the earlier one.
+///
+/// Sessions are kept until [`prune_before`](Self::prune_before) drops them.
+pub struct FocusTracker<C = SystemClock> {
+    clock: C,
+    sessions: Vec<FocusSession>,
+    current: Option<OngoingSession>,
+    last_time: Option<SystemTime>,
+}
+
+#[derive(Debug)]
+struct OngoingSession {
+    app: AppKey,
+    /// The pid of the instance that is frontmost.
+    pid: usize,
+    localized_name: Option<String>,
+    start: SystemTime,
+}
+
+impl FocusTracker<SystemClock> {
+    pub fn new() -> FocusTracker<SystemClock> {
+        FocusTracker::with_clock(SystemClock)
+    }
+}
+
+impl Default for FocusTracker<SystemClock> {
+    fn default() -> Self {
+        FocusTracker::new()
+    }
+}
+
+impl<C: Clock> FocusTracker<C> {
+    /// Creates a tracker that reads the time from `clock`.
+    pub fn with_clock(clock: C) -> FocusTracker<C> {
+        FocusTracker {
+            clock,
+            sessions: Vec::new(),
+            current: None,
+            last_time: None,
+        }
+    }
+
+    /// Records a notification at the time the watcher received it, or now if it has no
+    /// [`stamp`](AppNotification::stamp).
+    pub fn record(&mut self, notification: &AppNotification) {
+        let time = match notification.stamp {
+            Some(stamp) => stamp.time,
+            None => self.clock.now(),
+        };
+
+        self.record_at(notification, time);
+    }
+
+    /// Records a notification received at `time`.
+    pub fn record_at(&mut self, notification: &AppNotification, time: SystemTime) {
+        self.record_parts(notification.notification_type, &notification.app, time);
+    }
+
+    /// Records an event received at `time`.
+    pub fn record_event_at(&mut self, event: &AppEvent, time: SystemTime) {
+        self.record_parts(event.notification_type(), event.app(), time);
+    }
+
+    fn record_parts(
+        &mut self,
+        notification_type: NotificationType,
+        app: &RunningAppInfo,
+        time: SystemTime,
+    ) {
+        let time = match self.last_time {
+            Some(last_time) => time.max(last_time),
+            None => time,
+        };
+        self.last_time = Some(time);
+
+        let key = AppKey::from(app);
+
+        match notification_type {
+            NotificationType::Activated => match &mut self.current {
+                Some(current) if current.app == key => current.pid = app.pid,
+                _ => {
+                    self.end_session(time);
+                    self.current = Some(OngoingSession {
+                        app: key,
+                        pid: app.pid,
+                        localized_name: app.localized_name.clone(),
+                        start: time,
+                    });
+                }
+            },
+            NotificationType::Deactivated | NotificationType::Terminated => {
+                let is_current = self
+                    .current
+                    .as_ref()
+                    .is_some_and(|current| current.app == key && current.pid == app.pid);
+
+                if is_current {
+                    self.end_session(time);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_session(&mut self, time: SystemTime) {
+        if let Some(current) = self.current.take() {
+            self.sessions.push(FocusSession {
+                app: current.app,
+                localized_name: current.localized_name,
+                start: current.start,
+                end: time,
+            });
+        }
+    }
+
+    /// The ongoing session, ending now.
+    pub fn current_session(&self) -> Option<FocusSession> {
+        let current = self.current.as_ref()?;
+
+        Some(FocusSession {
+            app: current.app.clone(),
+            localized_name: current.localized_name.clone(),
+            start: current.start,
+            end: self.clock.now().max(current.start),
+        })
+    }
+
+    /// The finished sessions, oldest first.
+    pub fn sessions(&self) -> &[FocusSession] {
+        &self.sessions
+    }
+
+    fn all_sessions(&self) -> impl Iterator<Item = FocusSession> + '_ {
+        self.sessions.iter().cloned().chain(self.current_session())
+    }
+
+    /// How long `app` has been frontmost in total, including the ongoing session.
+    pub fn total(&self, app: &AppKey) -> Duration {
+        self.all_sessions()
+            .filter(|session| session.app == *app)
+            .map(|session| session.duration())
+            .sum()
+    }
+
+    /// How long each application has been frontmost in total, including the ongoing session.
+    pub fn totals(&self) -> HashMap<AppKey, Duration> {
+        let mut totals = HashMap::new();
+
+        for session in self.all_sessions() {
+            *totals.entry(session.app.clone()).or_default() += session.duration();
+        }
+
+        totals
+    }
+
+    /// How long each application was frontmost within `range`.
+    pub fn totals_in(&self, range: Range<SystemTime>) -> HashMap<AppKey, Duration> {
+        let mut totals = HashMap::new();
+
+        for session in self.all_sessions() {
+            let overlap = session.overlap(&range);
+
+            if !overlap.is_zero() {
+                *totals.entry(session.app.clone()).or_default() += overlap;
+            }
+        }
+
+        totals
+    }
+
+    /// Drops the finished sessions that ended before `time`.
+    pub fn prune_before(&mut self, time: SystemTime) {
+        self.sessions.retain(|session| session.end >= time);
+    }
+}
+
+impl<C> fmt::Debug for FocusTracker<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FocusTracker")
+            .field("sessions", &self.sessions)
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    fn app(pid: usize, bundle_identifier: &str) -> RunningAppInfo {
+        RunningAppInfo {
+            pid,
+            localized_name: None,
+            bundle_identifier: Some(bundle_identifier.to_string()),
+            bundle_url: None,
+        }
+    }
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn record(
+        tracker: &mut FocusTracker<impl Clock>,
+        notification_type: NotificationType,
+        app: &RunningAppInfo,
+        seconds: u64,
+    ) {
+        tracker.record_at(
+            &AppNotification {
+                notification_type,
+                app: app.clone(),
+                stamp: None,
+            },
+            at(seconds),
+        );
+    }
+
+    fn key(bundle_identifier: &str) -> AppKey {
+        AppKey::BundleIdentifier(bundle_identifier.to_string())
+    }
+
+    fn spans(tracker: &FocusTracker<impl Clock>) -> Vec<(AppKey, u64, u64)> {
+        tracker
+            .sessions()
+            .iter()
+            .map(|session| {
+                let seconds = |time: SystemTime| time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+                (
+                    session.app.clone(),
+                    seconds(session.start),
+                    seconds(session.end),
+                )
+            })
+            .collect()
+    }
+
+    /// A clock that reads a time the test sets.
+    fn fake_clock(seconds: u64) -> (Arc<Mutex<SystemTime>>, impl Clock) {
+        let now = Arc::new(Mutex::new(at(seconds)));
+        let clock = {
+            let now = now.clone();
+            move || *now.lock().unwrap()
+        };
+
+        (now, clock)
+    }
+
+    #[test]
+    fn activation_ends_session_without_deactivated() {
+        let mut tracker = FocusTracker::new();
+        let safari = app(42, "com.apple.Safari");
+        let mail = app(43, "com.apple.mail");
+
+        record(&mut tracker, NotificationType::Activated, &safari, 10);
+        record(&mut tracker, NotificationType::Activated, &mail, 25);
+        record(&mut tracker, NotificationType::Deactivated, &mail, 30);
+
+        assert_eq!(
+            spans(&tracker),
+            [
+                (key("com.apple.Safari"), 10, 25),
+                (key("com.apple.mail"), 25, 30),
+            ]
+        );
+        assert!(tracker.current_session().is_none());
+    }
+
+    #[test]
+    fn late_deactivated_is_ignored() {
+        let mut tracker = FocusTracker::new();
+        let safari = app(42, "com.apple.Safari");
+        let mail = app(43, "com.apple.mail");
+
+        record(&mut tracker, NotificationType::Activated, &safari, 10);
+        record(&mut tracker, NotificationType::Activated, &mail, 20);
+        // Safari's `Deactivated` arrives after Mail's `Activated`, timestamped before it.
+        record(&mut tracker, NotificationType::Deactivated, &safari, 15);
+        record(&mut tracker, NotificationType::Deactivated, &mail, 30);
+
+        assert_eq!(
+            spans(&tracker),
+            [
+                (key("com.apple.Safari"), 10, 20),
+                (key("com.apple.mail"), 20, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn out_of_order_times_are_clamped() {
+        let mut tracker = FocusTracker::new();
+        let safari = app(42, "com.apple.Safari");
+        let mail = app(43, "com.apple.mail");
+
+        record(&mut tracker, NotificationType::Activated, &safari, 20);
+        record(&mut tracker, NotificationType::Activated, &mail, 15);
+
+        assert_eq!(spans(&tracker), [(key("com.apple.Safari"), 20, 20)]);
+        assert_eq!(tracker.current_session().unwrap().start, at(20));
+    }
+
+    #[test]
+    fn termination_ends_frontmost_session() {
+        let mut tracker = FocusTracker::new();
+        let safari = app(42, "com.apple.Safari");
+        let mail = app(43, "com.apple.mail");
+
+        record(&mut tracker, NotificationType::Activated, &safari, 10);
+        // Terminating another application doesn't end the session.
+        record(&mut tracker, NotificationType::Terminated, &mail, 12);
+        record(&mut tracker, NotificationType::Terminated, &safari, 18);
+
+        assert_eq!(spans(&tracker), [(key("com.apple.Safari"), 10, 18)]);
+        assert!(tracker.current_session().is_none());
+    }
+
+    #[test]
+    fn other_instance_does_not_end_session() {
+        let mut tracker = FocusTracker::new();
+        let first = app(42, "com.apple.Terminal");
+        let second = app(43, "com.apple.Terminal");
+
+        record(&mut tracker, NotificationType::Activated, &first, 10);
+        record(&mut tracker, NotificationType::Terminated, &second, 12);
+        record(&mut tracker, NotificationType::Deactivated, &second, 13);
+        assert!(tracker.sessions().is_empty());
+
+        // Activating the second instance carries the session on, so the first one's
+        // termination doesn't end it either.
+        record(&mut tracker, NotificationType::Activated, &second, 14);
+        record(&mut tracker, NotificationType::Terminated, &first, 15);
+        assert!(tracker.sessions().is_empty());
+
+        record(&mut tracker, NotificationType::Deactivated, &second, 20);
+        assert_eq!(spans(&tracker), [(key("com.apple.Terminal"), 10, 20)]);
+    }
+
+    #[test]
+    fn totals_in_splits_sessions() {
+        let (now, clock) = fake_clock(40);
+        let mut tracker = FocusTracker::with_clock(clock);
+        let safari = app(42, "com.apple.Safari");
+        let mail = app(43, "com.apple.mail");
+
+        record(&mut tracker, NotificationType::Activated, &safari, 10);
+        record(&mut tracker, NotificationType::Activated, &mail, 20);
+        record(&mut tracker, NotificationType::Activated, &safari, 30);
+
+        let totals = tracker.totals_in(at(15)..at(35));
+        assert_eq!(totals[&key("com.apple.Safari")], Duration::from_secs(10));
+        assert_eq!(totals[&key("com.apple.mail")], Duration::from_secs(10));
+
+        // Ranges that only touch a session don't count it.
+        let totals = tracker.totals_in(at(0)..at(10));
+        assert!(totals.is_empty());
+
+        *now.lock().unwrap() = at(50);
+        assert_eq!(
+            tracker.total(&key("com.apple.Safari")),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn current_session_ends_now() {
+        let (now, clock) = fake_clock(5);
+        let mut tracker = FocusTracker::with_clock(clock);
+        let safari = app(42, "com.apple.Safari");
+
+        assert!(tracker.current_session().is_none());
+
+        record(&mut tracker, NotificationType::Activated, &safari, 10);
+
+        // A clock behind the session's start doesn't make it negative.
+        assert_eq!(tracker.current_session().unwrap().end, at(10));
+
+        *now.lock().unwrap() = at(25);
+        let session = tracker.current_session().unwrap();
+        assert_eq!(session.app, key("com.apple.Safari"));
+        assert_eq!(session.duration(), Duration::from_secs(15));
+
+        // Notifications without a stamp are recorded at the clock's time.
+        tracker.record(&AppNotification {
+            notification_type: NotificationType::Deactivated,
+            app: safari,
+            stamp: None,
+        });
+        assert_eq!(spans(&tracker), [(key("com.apple.Safari"), 10, 25)]);
+    }
+}
@@ -1,7 +1,43 @@
+mod app_event;
 mod app_notification;
+mod clock;
+mod combinators;
+mod error;
+mod event_sink;
+mod filter;
+mod focus;
+mod registry;
+mod stamp;
 
+#[cfg(target_os = "macos")]
 #[allow(clippy::module_inception)]
 mod app_watcher;
+#[cfg(target_os = "macos")]
+mod builder;
 
+#[cfg(feature = "record")]
+pub mod record;
+#[cfg(feature = "rules")]
+pub mod rules;
+
+#[cfg(all(target_os = "macos", feature = "async"))]
+mod stream;
+
+pub use app_event::*;
 pub use app_notification::*;
+#[cfg(target_os = "macos")]
 pub use app_watcher::*;
+#[cfg(target_os = "macos")]
+pub use builder::*;
+pub use clock::*;
+pub use combinators::*;
+pub use error::*;
+pub use event_sink::*;
+pub use filter::*;
+pub use focus::*;
+#[cfg(feature = "record")]
+pub use record::{RecordedNotification, Recorder, ReplayError, ReplaySpeed, Replayer};
+pub use registry::*;
+pub use stamp::*;
+#[cfg(all(target_os = "macos", feature = "async"))]
+pub use stream::*;
@@ -0,0 +1,381 @@
+//! Recording notification streams to JSON Lines files, and replaying them.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AppNotification, Clock, Disconnected, EventSink, SystemClock};
+
+/// A notification and the time it was received, as stored on one line of a recording.
+///
+/// The time is stored as fractional seconds since the Unix epoch, next to the notification's
+/// fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedNotification {
+    #[serde(with = "unix_seconds")]
+    pub time: SystemTime,
+    #[serde(flatten)]
+    pub notification: AppNotification,
+}
+
+mod unix_seconds {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs_f64(),
+            Err(error) => -error.duration().as_secs_f64(),
+        };
+
+        serializer.serialize_f64(seconds)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        use serde::de::Error;
+
+        let seconds = f64::deserialize(deserializer)?;
+        let duration = Duration::try_from_secs_f64(seconds.abs()).map_err(D::Error::custom)?;
+
+        let time = if seconds >= 0.0 {
+            UNIX_EPOCH.checked_add(duration)
+        } else {
+            UNIX_EPOCH.checked_sub(duration)
+        };
+
+        time.ok_or_else(|| D::Error::custom("timestamp out of range"))
+    }
+}
+
+/// Writes notifications to a JSON Lines recording, one [`RecordedNotification`] per line.
+///
+/// As an [`EventSink`], every notification is flushed as soon as it's written, and a write error
+/// stops the watcher.
+pub struct Recorder<W, C = SystemClock> {
+    writer: W,
+    clock: C,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates a recording at `path`, replacing any existing file.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Recorder<BufWriter<File>>> {
+        Ok(Recorder::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(writer: W) -> Recorder<W> {
+        Recorder::with_clock(writer, SystemClock)
+    }
+}
+
+impl<W: Write, C: Clock> Recorder<W, C> {
+    /// Creates a recorder that timestamps notifications without a
+    /// [`stamp`](AppNotification::stamp) with `clock`.
+    pub fn with_clock(writer: W, clock: C) -> Recorder<W, C> {
+        Recorder { writer, clock }
+    }
+
+    /// Writes `notification`, timestamped with the time the watcher received it, or now if it
+    /// has no [`stamp`](AppNotification::stamp).
+    pub fn record(&mut self, notification: &AppNotification) -> io::Result<()> {
+        let time = match notification.stamp {
+            Some(stamp) => stamp.time,
+            None => self.clock.now(),
+        };
+
+        self.record_at(notification, time)
+    }
+
+    /// Writes `notification`, timestamped `time`.
+    pub fn record_at(
+        &mut self,
+        notification: &AppNotification,
+        time: SystemTime,
+    ) -> io::Result<()> {
+        let recorded = RecordedNotification {
+            time,
+            notification: notification.clone(),
+        };
+
+        serde_json::to_writer(&mut self.writer, &recorded)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer, without flushing it.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W, C> EventSink for Recorder<W, C>
+where
+    W: Write + Send + 'static,
+    C: Clock,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        self.record(&notification)
+            .and_then(|()| self.flush())
+            .map_err(|_| Disconnected)
+    }
+}
+
+/// How fast a [`Replayer`] delivers notifications.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReplaySpeed {
+    /// With the delays between them at recording time.
+    Original,
+    /// With the recorded delays divided by the factor, so `2.0` replays twice as fast.
+    ///
+    /// A factor that isn't positive replays without any delay, and a delay too long for a
+    /// [`Duration`] is cut to [`Duration::MAX`].
+    Scaled(f64),
+    /// Without any delay.
+    AsFastAsPossible,
+}
+
+/// An error reading a recording.
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// A line isn't a [`RecordedNotification`]. Lines are numbered from 1.
+    Parse {
+        line: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "failed to read the recording: {error}"),
+            ReplayError::Parse { line, error } => {
+                write!(f, "invalid notification on line {line}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Io(error) => Some(error),
+            ReplayError::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+/// Feeds a recording made by a [`Recorder`] into an [`EventSink`], on any platform.
+///
+/// ```
+/// use cocoa_utils::{AppNotification, ReplaySpeed, Replayer};
+///
+/// let recording = r#"{"time":0.0,"notification_type":"activated","app":{"pid":42,"localized_name":"Safari","bundle_identifier":"com.apple.Safari","bundle_url":null}}"#;
+///
+/// let (sender, receiver) = std::sync::mpsc::channel::<AppNotification>();
+/// let count = Replayer::new(recording.as_bytes())
+///     .speed(ReplaySpeed::AsFastAsPossible)
+///     .replay(sender)
+///     .unwrap();
+///
+/// assert_eq!(count, 1);
+/// assert_eq!(receiver.recv().unwrap().app.pid, 42);
+/// ```
+pub struct Replayer<R> {
+    reader: R,
+    speed: ReplaySpeed,
+    sleep: Box<dyn FnMut(Duration)>,
+}
+
+impl Replayer<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Replayer<BufReader<File>>> {
+        Ok(Replayer::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Replayer<R> {
+    /// Creates a replayer that reads a recording from `reader`, at [`ReplaySpeed::Original`].
+    pub fn new(reader: R) -> Replayer<R> {
+        Replayer {
+            reader,
+            speed: ReplaySpeed::Original,
+            sleep: Box::new(std::thread::sleep),
+        }
+    }
+
+    pub fn speed(mut self, speed: ReplaySpeed) -> Replayer<R> {
+        self.speed = speed;
+        self
+    }
+
+    /// Replaces `std::thread::sleep` for waiting between notifications, e.g. with a fake clock.
+    pub fn with_sleep(mut self, sleep: impl FnMut(Duration) + 'static) -> Replayer<R> {
+        self.sleep = Box::new(sleep);
+        self
+    }
+
+    /// Reads the recorded notifications without any delay. Blank lines are skipped.
+    pub fn recordings(self) -> impl Iterator<Item = Result<RecordedNotification, ReplayError>> {
+        read_recordings(self.reader)
+    }
+
+    /// Delivers every notification in the recording to `sink`, returning how many were
+    /// delivered.
+    ///
+    /// Stops early, without an error, if the sink's receiver disconnects.
+    pub fn replay(self, mut sink: impl EventSink) -> Result<usize, ReplayError> {
+        let Replayer {
+            reader,
+            speed,
+            mut sleep,
+        } = self;
+
+        let mut previous_time = None;
+        let mut count = 0;
+
+        for recorded in read_recordings(reader) {
+            let recorded = recorded?;
+
+            if let Some(previous_time) = previous_time {
+                let delay = recorded
+                    .time
+                    .duration_since(previous_time)
+                    .unwrap_or_default();
+
+                let delay = match speed {
+                    ReplaySpeed::Original => delay,
+                    ReplaySpeed::Scaled(factor) if factor > 0.0 => {
+                        Duration::try_from_secs_f64(delay.as_secs_f64() / factor)
+                            .unwrap_or(Duration::MAX)
+                    }
+                    ReplaySpeed::Scaled(_) | ReplaySpeed::AsFastAsPossible => Duration::ZERO,
+                };
+
+                if !delay.is_zero() {
+                    sleep(delay);
+                }
+            }
+
+            previous_time = Some(recorded.time);
+
+            if sink.send(recorded.notification).is_err() {
+                break;
+            }
+
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+fn read_recordings(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<RecordedNotification, ReplayError>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(index, line)| {
+            serde_json::from_str(&line?).map_err(|error| ReplayError::Parse {
+                line: index + 1,
+                error,
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+    use crate::{EventStamp, NotificationType, RunningAppInfo};
+
+    fn notification(stamp: Option<EventStamp>) -> AppNotification {
+        AppNotification {
+            notification_type: NotificationType::Activated,
+            app: RunningAppInfo {
+                pid: 42,
+                localized_name: None,
+                bundle_identifier: None,
+                bundle_url: None,
+            },
+            stamp,
+        }
+    }
+
+    #[test]
+    fn records_stamp_time() {
+        let stamp = EventStamp {
+            sequence: 0,
+            time: UNIX_EPOCH + Duration::from_secs(10),
+            monotonic: Duration::ZERO,
+        };
+        let mut recorder =
+            Recorder::with_clock(Vec::new(), || UNIX_EPOCH + Duration::from_secs(99));
+
+        recorder.record(&notification(Some(stamp))).unwrap();
+        recorder.record(&notification(None)).unwrap();
+
+        let times: Vec<SystemTime> = Replayer::new(&recorder.into_inner()[..])
+            .recordings()
+            .map(|recorded| recorded.unwrap().time)
+            .collect();
+        assert_eq!(
+            times,
+            [
+                UNIX_EPOCH + Duration::from_secs(10),
+                UNIX_EPOCH + Duration::from_secs(99),
+            ]
+        );
+    }
+
+    #[test]
+    fn scaled_delays_saturate() {
+        let mut recorder = Recorder::new(Vec::new());
+        recorder.record_at(&notification(None), UNIX_EPOCH).unwrap();
+        recorder
+            .record_at(&notification(None), UNIX_EPOCH + Duration::from_secs(2))
+            .unwrap();
+        let recording = recorder.into_inner();
+
+        for (factor, expected) in [
+            (4.0, Duration::from_millis(500)),
+            (f64::MIN_POSITIVE, Duration::MAX),
+            (0.0, Duration::ZERO),
+            (f64::NAN, Duration::ZERO),
+        ] {
+            let delays = Rc::new(RefCell::new(Vec::new()));
+            let count = Replayer::new(&recording[..])
+                .speed(ReplaySpeed::Scaled(factor))
+                .with_sleep({
+                    let delays = delays.clone();
+                    move |delay| delays.borrow_mut().push(delay)
+                })
+                .replay(|_| ())
+                .unwrap();
+
+            assert_eq!(count, 2);
+
+            let expected: &[Duration] = if expected.is_zero() { &[] } else { &[expected] };
+            assert_eq!(*delays.borrow(), expected, "{factor}");
+        }
+    }
+}
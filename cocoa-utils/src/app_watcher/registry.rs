@@ -0,0 +1,520 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{AppEvent, AppNotification, NotificationType, RunningAppInfo};
+
+/// What an [`AppRegistry`] knows about a running application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppState {
+    /// The application's details as of the last notification about it.
+    pub app: RunningAppInfo,
+    /// `false` between the `Launching` and `Launched` notifications.
+    pub finished_launching: bool,
+    pub hidden: bool,
+    /// `true` for the frontmost application.
+    pub active: bool,
+}
+
+impl AppState {
+    fn new(app: RunningAppInfo, finished_launching: bool) -> AppState {
+        AppState {
+            app,
+            finished_launching,
+            hidden: false,
+            active: false,
+        }
+    }
+}
+
+/// A change an [`AppRegistry`] made while applying a notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppChange {
+    /// An application the registry didn't know about was added.
+    Added(AppState),
+    /// A terminated application was removed.
+    Removed(AppState),
+    /// The state of a known application changed.
+    Changed { old: AppState, new: AppState },
+    /// Another application, identified by its pid, became frontmost, or none is anymore.
+    FrontmostChanged {
+        old: Option<usize>,
+        new: Option<usize>,
+    },
+}
+
+/// Identifies a callback registered with [`AppRegistry::subscribe`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Subscriber = Box<dyn FnMut(&AppChange) + Send>;
+
+/// Tracks which applications are running, and which one is frontmost, from a stream of
+/// application notifications.
+///
+/// The registry only knows what it's told: applications that were already running when the
+/// notifications started are added as soon as a notification mentions them. Applications are
+/// keyed by pid.
+///
+/// ```
+/// use cocoa_utils::{AppNotification, AppRegistry, NotificationType, RunningAppInfo};
+///
+/// let safari = RunningAppInfo {
+///     pid: 42,
+///     localized_name: Some("Safari".to_string()),
+///     bundle_identifier: Some("com.apple.Safari".to_string()),
+///     bundle_url: None,
+/// };
+///
+/// let mut registry = AppRegistry::new();
+/// registry.apply(&AppNotification::new(NotificationType::Activated, safari));
+///
+/// assert_eq!(registry.frontmost().unwrap().app.pid, 42);
+/// ```
+#[derive(Default)]
+pub struct AppRegistry {
+    apps: BTreeMap<usize, AppState>,
+    frontmost: Option<usize>,
+    subscribers: Vec<(SubscriptionId, Subscriber)>,
+    next_subscription: u64,
+}
+
+impl AppRegistry {
+    pub fn new() -> AppRegistry {
+        AppRegistry::default()
+    }
+
+    /// Updates the registry from a notification, returning the changes it made, which are also
+    /// passed to every subscriber.
+    pub fn apply(&mut self, notification: &AppNotification) -> Vec<AppChange> {
+        self.apply_parts(notification.notification_type, &notification.app)
+    }
+
+    /// Like [`apply`](Self::apply), for an [`AppEvent`].
+    ///
+    /// Unlike the flattened notification, an [`AppEvent::AlreadyRunning`] event tells the
+    /// registry whether the application is hidden and has finished launching.
+    pub fn apply_event(&mut self, event: &AppEvent) -> Vec<AppChange> {
+        match *event {
+            AppEvent::AlreadyRunning {
+                ref app,
+                hidden,
+                finished_launching,
+            } => self.apply_with(app, |state| {
+                state.hidden = hidden;
+                state.finished_launching = finished_launching;
+            }),
+            _ => self.apply_parts(event.notification_type(), event.app()),
+        }
+    }
+
+    fn apply_with(
+        &mut self,
+        app: &RunningAppInfo,
+        update: impl FnOnce(&mut AppState),
+    ) -> Vec<AppChange> {
+        let mut changes = Vec::new();
+        self.update(app, false, &mut changes, update);
+        self.notify(&changes);
+        changes
+    }
+
+    fn apply_parts(
+        &mut self,
+        notification_type: NotificationType,
+        app: &RunningAppInfo,
+    ) -> Vec<AppChange> {
+        let mut changes = Vec::new();
+        let pid = app.pid;
+
+        match notification_type {
+            NotificationType::Terminated => {
+                if self.frontmost == Some(pid) {
+                    self.set_frontmost(None, &mut changes);
+                }
+
+                if let Some(state) = self.apps.remove(&pid) {
+                    changes.push(AppChange::Removed(state));
+                }
+            }
+            NotificationType::Activated => {
+                self.update(app, false, &mut changes, |state| state.active = true);
+
+                if self.frontmost != Some(pid) {
+                    if let Some(previous) = self.frontmost {
+                        self.update_known(previous, &mut changes, |state| state.active = false);
+                    }
+
+                    self.set_frontmost(Some(pid), &mut changes);
+                }
+            }
+            NotificationType::Deactivated => {
+                self.update(app, false, &mut changes, |state| state.active = false);
+
+                if self.frontmost == Some(pid) {
+                    self.set_frontmost(None, &mut changes);
+                }
+            }
+            NotificationType::Launching => self.update(app, true, &mut changes, |_| {}),
+            NotificationType::Launched => self.update(app, false, &mut changes, |state| {
+                state.finished_launching = true
+            }),
+            NotificationType::Hidden => {
+                self.update(app, false, &mut changes, |state| state.hidden = true)
+            }
+            NotificationType::Unhidden => {
+                self.update(app, false, &mut changes, |state| state.hidden = false)
+            }
+            NotificationType::AlreadyRunning => self.update(app, false, &mut changes, |_| {}),
+        }
+
+        self.notify(&changes);
+        changes
+    }
+
+    fn notify(&mut self, changes: &[AppChange]) {
+        for change in changes {
+            for (_, subscriber) in &mut self.subscribers {
+                subscriber(change);
+            }
+        }
+    }
+
+    /// Adds `app` if it's unknown, then refreshes its details and applies `update`.
+    ///
+    /// An unknown application is assumed to have finished launching, unless `launching`.
+    fn update(
+        &mut self,
+        app: &RunningAppInfo,
+        launching: bool,
+        changes: &mut Vec<AppChange>,
+        update: impl FnOnce(&mut AppState),
+    ) {
+        match self.apps.get_mut(&app.pid) {
+            Some(state) => {
+                let old = state.clone();
+                state.app = app.clone();
+                update(state);
+
+                if *state != old {
+                    changes.push(AppChange::Changed {
+                        old,
+                        new: state.clone(),
+                    });
+                }
+            }
+            None => {
+                let mut state = AppState::new(app.clone(), !launching);
+                update(&mut state);
+
+                changes.push(AppChange::Added(state.clone()));
+                self.apps.insert(app.pid, state);
+            }
+        }
+    }
+
+    fn update_known(
+        &mut self,
+        pid: usize,
+        changes: &mut Vec<AppChange>,
+        update: impl FnOnce(&mut AppState),
+    ) {
+        if let Some(state) = self.apps.get_mut(&pid) {
+            let old = state.clone();
+            update(state);
+
+            if *state != old {
+                changes.push(AppChange::Changed {
+                    old,
+                    new: state.clone(),
+                });
+            }
+        }
+    }
+
+    fn set_frontmost(&mut self, frontmost: Option<usize>, changes: &mut Vec<AppChange>) {
+        changes.push(AppChange::FrontmostChanged {
+            old: self.frontmost,
+            new: frontmost,
+        });
+        self.frontmost = frontmost;
+    }
+
+    /// Calls `callback` with every change the registry makes from now on.
+    pub fn subscribe<F>(&mut self, callback: F) -> SubscriptionId
+    where
+        F: FnMut(&AppChange) + Send + 'static,
+    {
+        let id = SubscriptionId(self.next_subscription);
+        self.next_subscription += 1;
+        self.subscribers.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Removes a callback registered with [`subscribe`](Self::subscribe). Returns `false` if it
+    /// was already removed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers
+            .retain(|(subscription, _)| *subscription != id);
+        self.subscribers.len() != len
+    }
+
+    /// Returns the state of the application with the given pid.
+    pub fn get(&self, pid: usize) -> Option<&AppState> {
+        self.apps.get(&pid)
+    }
+
+    /// Returns every running instance of the application with the given bundle identifier.
+    pub fn by_bundle_identifier<'a>(
+        &'a self,
+        bundle_identifier: &'a str,
+    ) -> impl Iterator<Item = &'a AppState> + 'a {
+        self.apps
+            .values()
+            .filter(move |state| state.app.bundle_identifier.as_deref() == Some(bundle_identifier))
+    }
+
+    /// Returns the frontmost application, if the registry knows it.
+    pub fn frontmost(&self) -> Option<&AppState> {
+        self.frontmost.and_then(|pid| self.apps.get(&pid))
+    }
+
+    /// Returns every known application, ordered by pid.
+    pub fn apps(&self) -> impl Iterator<Item = &AppState> + '_ {
+        self.apps.values()
+    }
+
+    /// Returns the applications that are hidden.
+    pub fn hidden(&self) -> impl Iterator<Item = &AppState> + '_ {
+        self.apps.values().filter(|state| state.hidden)
+    }
+
+    pub fn contains(&self, pid: usize) -> bool {
+        self.apps.contains_key(&pid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.apps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.apps.is_empty()
+    }
+}
+
+impl fmt::Debug for AppRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppRegistry")
+            .field("apps", &self.apps)
+            .field("frontmost", &self.frontmost)
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn app(pid: usize, bundle_identifier: &str) -> RunningAppInfo {
+        RunningAppInfo {
+            pid,
+            localized_name: None,
+            bundle_identifier: Some(bundle_identifier.to_string()),
+            bundle_url: None,
+        }
+    }
+
+    fn notify(
+        registry: &mut AppRegistry,
+        notification_type: NotificationType,
+        app: &RunningAppInfo,
+    ) -> Vec<AppChange> {
+        registry.apply(&AppNotification {
+            notification_type,
+            app: app.clone(),
+            stamp: None,
+        })
+    }
+
+    #[test]
+    fn launching_then_launched() {
+        let mut registry = AppRegistry::new();
+        let safari = app(42, "com.apple.Safari");
+
+        let changes = notify(&mut registry, NotificationType::Launching, &safari);
+        let launching = AppState::new(safari.clone(), false);
+        assert_eq!(changes, vec![AppChange::Added(launching.clone())]);
+
+        let changes = notify(&mut registry, NotificationType::Launched, &safari);
+        let launched = AppState::new(safari.clone(), true);
+        assert_eq!(
+            changes,
+            vec![AppChange::Changed {
+                old: launching,
+                new: launched.clone(),
+            }]
+        );
+        assert_eq!(registry.get(42), Some(&launched));
+
+        // A second `Launched` changes nothing.
+        assert!(notify(&mut registry, NotificationType::Launched, &safari).is_empty());
+    }
+
+    #[test]
+    fn activation_moves_frontmost() {
+        let mut registry = AppRegistry::new();
+        let safari = app(42, "com.apple.Safari");
+        let mail = app(43, "com.apple.mail");
+
+        notify(&mut registry, NotificationType::Activated, &safari);
+        assert_eq!(registry.frontmost().unwrap().app.pid, 42);
+
+        let changes = notify(&mut registry, NotificationType::Activated, &mail);
+        assert!(changes.contains(&AppChange::FrontmostChanged {
+            old: Some(42),
+            new: Some(43),
+        }));
+        assert_eq!(registry.frontmost().unwrap().app.pid, 43);
+        assert!(!registry.get(42).unwrap().active);
+        assert!(registry.get(43).unwrap().active);
+
+        // Deactivating an application that isn't frontmost leaves the frontmost one alone.
+        notify(&mut registry, NotificationType::Deactivated, &safari);
+        assert_eq!(registry.frontmost().unwrap().app.pid, 43);
+
+        let changes = notify(&mut registry, NotificationType::Deactivated, &mail);
+        assert!(changes.contains(&AppChange::FrontmostChanged {
+            old: Some(43),
+            new: None,
+        }));
+        assert!(registry.frontmost().is_none());
+        assert!(!registry.get(43).unwrap().active);
+    }
+
+    #[test]
+    fn terminating_frontmost_app() {
+        let mut registry = AppRegistry::new();
+        let safari = app(42, "com.apple.Safari");
+
+        notify(&mut registry, NotificationType::Activated, &safari);
+        let state = registry.get(42).unwrap().clone();
+
+        let changes = notify(&mut registry, NotificationType::Terminated, &safari);
+        assert_eq!(
+            changes,
+            vec![
+                AppChange::FrontmostChanged {
+                    old: Some(42),
+                    new: None,
+                },
+                AppChange::Removed(state),
+            ]
+        );
+        assert!(registry.frontmost().is_none());
+        assert!(registry.is_empty());
+
+        // Terminating an unknown application changes nothing.
+        assert!(notify(&mut registry, NotificationType::Terminated, &safari).is_empty());
+    }
+
+    #[test]
+    fn hide_and_unhide() {
+        let mut registry = AppRegistry::new();
+        let safari = app(42, "com.apple.Safari");
+
+        notify(&mut registry, NotificationType::Hidden, &safari);
+        assert!(registry.get(42).unwrap().hidden);
+        assert_eq!(registry.hidden().count(), 1);
+
+        let changes = notify(&mut registry, NotificationType::Unhidden, &safari);
+        assert!(matches!(
+            &changes[..],
+            [AppChange::Changed { old, new }] if old.hidden && !new.hidden
+        ));
+        assert_eq!(registry.hidden().count(), 0);
+    }
+
+    #[test]
+    fn already_running_event_sets_state() {
+        let mut registry = AppRegistry::new();
+        let safari = app(42, "com.apple.Safari");
+
+        let changes = registry.apply_event(&AppEvent::AlreadyRunning {
+            app: safari.clone(),
+            hidden: true,
+            finished_launching: false,
+        });
+
+        let expected = AppState {
+            app: safari.clone(),
+            finished_launching: false,
+            hidden: true,
+            active: false,
+        };
+        assert_eq!(changes, vec![AppChange::Added(expected.clone())]);
+        assert_eq!(registry.get(42), Some(&expected));
+
+        // Other events go through the same path as notifications.
+        registry.apply_event(&AppEvent::Unhidden { app: safari });
+        assert!(!registry.get(42).unwrap().hidden);
+    }
+
+    #[test]
+    fn subscribe_and_unsubscribe() {
+        let mut registry = AppRegistry::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let id = registry.subscribe({
+            let received = received.clone();
+            move |change| received.lock().unwrap().push(change.clone())
+        });
+
+        let changes = notify(
+            &mut registry,
+            NotificationType::Activated,
+            &app(42, "com.apple.Safari"),
+        );
+        assert_eq!(*received.lock().unwrap(), changes);
+
+        assert!(registry.unsubscribe(id));
+        assert!(!registry.unsubscribe(id));
+
+        notify(
+            &mut registry,
+            NotificationType::Activated,
+            &app(43, "com.apple.mail"),
+        );
+        assert_eq!(*received.lock().unwrap(), changes);
+    }
+
+    #[test]
+    fn by_bundle_identifier_returns_every_instance() {
+        let mut registry = AppRegistry::new();
+
+        notify(
+            &mut registry,
+            NotificationType::Launched,
+            &app(42, "com.apple.Terminal"),
+        );
+        notify(
+            &mut registry,
+            NotificationType::Launched,
+            &app(43, "com.apple.Safari"),
+        );
+        notify(
+            &mut registry,
+            NotificationType::Launched,
+            &app(44, "com.apple.Terminal"),
+        );
+
+        let pids: Vec<usize> = registry
+            .by_bundle_identifier("com.apple.Terminal")
+            .map(|state| state.app.pid)
+            .collect();
+        assert_eq!(pids, [42, 44]);
+        assert_eq!(registry.by_bundle_identifier("com.apple.mail").count(), 0);
+    }
+}
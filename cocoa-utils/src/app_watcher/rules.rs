@@ -0,0 +1,1080 @@
+//! Declarative rules that run actions when applications launch, terminate, activate and so on.
+//!
+//! A [`RuleSet`] is loaded from TOML. Each rule is triggered by notification types, bundle
+//! identifiers and name patterns, can require other applications to be running, hidden or
+//! frontmost, and lists the actions to run:
+//!
+//! ```toml
+//! [[rule]]
+//! name = "hide Slack while presenting"
+//! on = ["activated"]
+//! bundle_identifiers = ["com.apple.iWork.Keynote"]
+//! when = { running = ["com.tinyspeck.slackmacgap"] }
+//! actions = [
+//!     { type = "hide", bundle_identifier = "com.tinyspeck.slackmacgap" },
+//!     { type = "shell", command = "say \"$COCOA_UTILS_LOCALIZED_NAME is up\"" },
+//! ]
+//! ```
+//!
+//! A [`RuleEngine`] is an [`EventSink`] that tracks the running applications in an
+//! [`AppRegistry`] and dispatches the actions of matching rules to an [`ActionExecutor`]:
+//! `SystemExecutor` on macOS, or a [`RecordingExecutor`] to test rules on any platform.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{
+    plist, AppEvent, AppFilter, AppKey, AppNotification, AppRegistry, AppState, Disconnected,
+    EventSink, EventStamp, NotificationType,
+};
+
+/// A list of rules, loaded from the `[[rule]]` tables of a TOML document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSet {
+    #[serde(rename = "rule", default)]
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn from_toml(toml: &str) -> Result<RuleSet, RulesError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<RuleSet, RulesError> {
+        RuleSet::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the rules that `notification` triggers and whose conditions hold in `registry`,
+    /// in the order they were declared.
+    pub fn matching<'a>(
+        &'a self,
+        notification: &'a AppNotification,
+        registry: &'a AppRegistry,
+    ) -> impl Iterator<Item = &'a Rule> + 'a {
+        self.rules
+            .iter()
+            .filter(move |rule| rule.triggered_by(notification) && rule.when.is_met(registry))
+    }
+}
+
+/// What to do when an application notification matches.
+///
+/// A rule is triggered by notifications of any of the types in `on`, about an application that
+/// matches any of `bundle_identifiers` or `name_patterns`, with the same semantics as an
+/// [`AppFilter`]. Empty lists match every notification type or every application.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Identifies the rule in errors, and to shell commands.
+    pub name: String,
+    #[serde(rename = "on", default)]
+    pub notification_types: Vec<NotificationType>,
+    #[serde(default)]
+    pub bundle_identifiers: Vec<String>,
+    #[serde(default)]
+    pub name_patterns: Vec<String>,
+    #[serde(default)]
+    pub when: Conditions,
+    pub actions: Vec<Action>,
+}
+
+impl Rule {
+    /// Returns `true` if `notification` triggers the rule, regardless of its conditions.
+    pub fn triggered_by(&self, notification: &AppNotification) -> bool {
+        (self.notification_types.is_empty()
+            || self
+                .notification_types
+                .contains(&notification.notification_type))
+            && self.filter().matches_app(&notification.app)
+    }
+
+    fn filter(&self) -> AppFilter {
+        let filter = self
+            .bundle_identifiers
+            .iter()
+            .fold(AppFilter::new(), |filter, bundle_identifier| {
+                filter.bundle_identifier(bundle_identifier.as_str())
+            });
+
+        self.name_patterns.iter().fold(filter, |filter, pattern| {
+            filter.name_pattern(pattern.as_str())
+        })
+    }
+}
+
+/// Requirements on the other running applications, all of which must hold for a rule to run.
+///
+/// Applications are identified by bundle identifier, and are checked against the engine's
+/// [`AppRegistry`], which only knows the applications it has been notified about; start the
+/// watcher in snapshot mode for it to know those that were already running.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Conditions {
+    pub running: Vec<String>,
+    pub not_running: Vec<String>,
+    /// Applications that must be running and hidden.
+    pub hidden: Vec<String>,
+    /// Applications that must be running and not hidden.
+    pub visible: Vec<String>,
+    /// The application that must be frontmost.
+    pub frontmost: Option<String>,
+}
+
+impl Conditions {
+    pub fn is_met(&self, registry: &AppRegistry) -> bool {
+        let any = |bundle_identifier: &String, predicate: fn(&AppState) -> bool| {
+            registry
+                .by_bundle_identifier(bundle_identifier)
+                .any(predicate)
+        };
+
+        self.running.iter().all(|id| any(id, |_| true))
+            && !self.not_running.iter().any(|id| any(id, |_| true))
+            && self.hidden.iter().all(|id| any(id, |state| state.hidden))
+            && self.visible.iter().all(|id| any(id, |state| !state.hidden))
+            && match &self.frontmost {
+                Some(bundle_identifier) => registry.frontmost().is_some_and(|state| {
+                    state.app.bundle_identifier.as_ref() == Some(bundle_identifier)
+                }),
+                None => true,
+            }
+    }
+}
+
+/// Something a rule does.
+///
+/// The `Hide`, `Unhide` and `Activate` actions apply to every running instance of the
+/// application with the given bundle identifier, or without one, to the application the
+/// notification is about.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Action {
+    /// Runs `command` with `/bin/sh -c`, with the notification in environment variables; see
+    /// [`ActionContext::environment`].
+    Shell {
+        command: String,
+    },
+    Hide {
+        bundle_identifier: Option<String>,
+    },
+    Unhide {
+        bundle_identifier: Option<String>,
+    },
+    Activate {
+        bundle_identifier: Option<String>,
+    },
+    /// Posts a notification named `name` to the process's default notification center, or with
+    /// `distributed`, to every process of the user's session. Its `userInfo` is
+    /// [`ActionContext::user_info`].
+    Notify {
+        name: String,
+        #[serde(default)]
+        distributed: bool,
+    },
+}
+
+/// The rule and notification an action runs for.
+#[derive(Debug, Copy, Clone)]
+pub struct ActionContext<'a> {
+    pub rule: &'a Rule,
+    pub notification: &'a AppNotification,
+}
+
+impl ActionContext<'_> {
+    /// The environment variables shell commands run with:
+    ///
+    /// - `COCOA_UTILS_RULE`, the rule's name
+    /// - `COCOA_UTILS_NOTIFICATION_TYPE`, e.g. `activated`
+    /// - `COCOA_UTILS_PID`
+    /// - `COCOA_UTILS_BUNDLE_IDENTIFIER`, `COCOA_UTILS_LOCALIZED_NAME` and
+    ///   `COCOA_UTILS_BUNDLE_PATH`, when the application has them
+    /// - `COCOA_UTILS_SEQUENCE`, when the notification came from a watcher
+    pub fn environment(&self) -> Vec<(&'static str, String)> {
+        let app = &self.notification.app;
+
+        let mut environment = vec![
+            ("COCOA_UTILS_RULE", self.rule.name.clone()),
+            (
+                "COCOA_UTILS_NOTIFICATION_TYPE",
+                self.notification.notification_type.as_str().to_string(),
+            ),
+            ("COCOA_UTILS_PID", app.pid.to_string()),
+        ];
+
+        if let Some(bundle_identifier) = &app.bundle_identifier {
+            environment.push(("COCOA_UTILS_BUNDLE_IDENTIFIER", bundle_identifier.clone()));
+        }
+        if let Some(localized_name) = &app.localized_name {
+            environment.push(("COCOA_UTILS_LOCALIZED_NAME", localized_name.clone()));
+        }
+        if let Some(bundle_path) = app.bundle_path() {
+            environment.push((
+                "COCOA_UTILS_BUNDLE_PATH",
+                bundle_path.to_string_lossy().into_owned(),
+            ));
+        }
+        if let Some(stamp) = &self.notification.stamp {
+            environment.push(("COCOA_UTILS_SEQUENCE", stamp.sequence.to_string()));
+        }
+
+        environment
+    }
+
+    /// The `userInfo` of posted notifications: the same values as
+    /// [`environment`](Self::environment), under the lowercase names without the prefix, e.g.
+    /// `bundle_identifier`.
+    pub fn user_info(&self) -> plist::Dictionary {
+        self.environment()
+            .into_iter()
+            .map(|(name, value)| {
+                let key = name.trim_start_matches("COCOA_UTILS_").to_lowercase();
+                (key, plist::Value::String(value))
+            })
+            .collect()
+    }
+}
+
+/// An action that failed to run.
+#[derive(Debug)]
+pub enum ActionError {
+    /// The shell command couldn't be started.
+    Spawn(io::Error),
+    /// No running application matched the action's target.
+    NotRunning(AppKey),
+    /// The application refused to be hidden, unhidden or activated.
+    Refused(AppKey),
+    /// The process has no default notification center to post to.
+    NoNotificationCenter,
+    /// An error from a custom [`ActionExecutor`].
+    Other(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::Spawn(error) => write!(f, "failed to start the shell command: {error}"),
+            ActionError::NotRunning(app) => write!(f, "{app} isn't running"),
+            ActionError::Refused(app) => write!(f, "{app} refused the request"),
+            ActionError::NoNotificationCenter => {
+                write!(f, "there is no default notification center")
+            }
+            ActionError::Other(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ActionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ActionError::Spawn(error) => Some(error),
+            ActionError::NotRunning(_)
+            | ActionError::Refused(_)
+            | ActionError::NoNotificationCenter => None,
+            ActionError::Other(error) => Some(&**error),
+        }
+    }
+}
+
+/// An action of a rule that failed, as reported by a [`RuleEngine`].
+#[derive(Debug)]
+pub struct ActionFailure {
+    pub rule: String,
+    pub action: Action,
+    pub error: ActionError,
+}
+
+impl fmt::Display for ActionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rule \"{}\" failed: {}", self.rule, self.error)
+    }
+}
+
+impl Error for ActionFailure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// An error loading a [`RuleSet`].
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(error) => write!(f, "failed to read the rules: {error}"),
+            RulesError::Toml(error) => write!(f, "invalid rules: {error}"),
+        }
+    }
+}
+
+impl Error for RulesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RulesError::Io(error) => Some(error),
+            RulesError::Toml(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for RulesError {
+    fn from(error: io::Error) -> Self {
+        RulesError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for RulesError {
+    fn from(error: toml::de::Error) -> Self {
+        RulesError::Toml(error)
+    }
+}
+
+/// Runs the actions of the rules a [`RuleEngine`] matches.
+///
+/// Implemented for closures.
+pub trait ActionExecutor {
+    fn execute(&mut self, action: &Action, context: &ActionContext<'_>) -> Result<(), ActionError>;
+}
+
+impl<F> ActionExecutor for F
+where
+    F: FnMut(&Action, &ActionContext<'_>) -> Result<(), ActionError>,
+{
+    fn execute(&mut self, action: &Action, context: &ActionContext<'_>) -> Result<(), ActionError> {
+        self(action, context)
+    }
+}
+
+/// An action a [`RecordingExecutor`] was asked to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutedAction {
+    pub rule: String,
+    pub action: Action,
+    pub notification: AppNotification,
+}
+
+/// An executor that records the actions instead of running them, to test rules.
+///
+/// ```
+/// use cocoa_utils::rules::{Action, RecordingExecutor, RuleEngine, RuleSet};
+/// use cocoa_utils::{AppNotification, NotificationType, RunningAppInfo};
+///
+/// let rules = RuleSet::from_toml(
+///     r#"
+///     [[rule]]
+///     name = "log Xcode quitting"
+///     on = ["terminated"]
+///     bundle_identifiers = ["com.apple.dt.Xcode"]
+///     actions = [{ type = "shell", command = "echo bye" }]
+///     "#,
+/// )
+/// .unwrap();
+///
+/// let mut engine = RuleEngine::new(rules, RecordingExecutor::new());
+/// engine.handle(&AppNotification::new(
+///     NotificationType::Terminated,
+///     RunningAppInfo {
+///         pid: 42,
+///         localized_name: Some("Xcode".to_string()),
+///         bundle_identifier: Some("com.apple.dt.Xcode".to_string()),
+///         bundle_url: None,
+///     },
+/// ));
+///
+/// let executed = engine.executor().executed();
+/// assert_eq!(executed.len(), 1);
+/// assert_eq!(
+///     executed[0].action,
+///     Action::Shell {
+///         command: "echo bye".to_string()
+///     }
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecordingExecutor {
+    executed: Vec<ExecutedAction>,
+}
+
+impl RecordingExecutor {
+    pub fn new() -> RecordingExecutor {
+        RecordingExecutor::default()
+    }
+
+    /// Returns the actions recorded so far, in the order they were run.
+    pub fn executed(&self) -> &[ExecutedAction] {
+        &self.executed
+    }
+
+    /// Returns the actions recorded so far, and forgets them.
+    pub fn take(&mut self) -> Vec<ExecutedAction> {
+        std::mem::take(&mut self.executed)
+    }
+}
+
+impl ActionExecutor for RecordingExecutor {
+    fn execute(&mut self, action: &Action, context: &ActionContext<'_>) -> Result<(), ActionError> {
+        self.executed.push(ExecutedAction {
+            rule: context.rule.name.clone(),
+            action: action.clone(),
+            notification: context.notification.clone(),
+        });
+        Ok(())
+    }
+}
+
+/// Runs actions for real.
+///
+/// Shell commands run in the background, so a slow command doesn't hold up the watcher; only a
+/// failure to start them is reported.
+#[cfg(target_os = "macos")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemExecutor;
+
+#[cfg(target_os = "macos")]
+impl ActionExecutor for SystemExecutor {
+    fn execute(&mut self, action: &Action, context: &ActionContext<'_>) -> Result<(), ActionError> {
+        use crate::{DistributedNotificationCenter, NotificationCenter, RunningApplication};
+
+        let applications = |bundle_identifier: &Option<String>| {
+            let (key, applications) = match bundle_identifier {
+                Some(bundle_identifier) => (
+                    AppKey::BundleIdentifier(bundle_identifier.clone()),
+                    RunningApplication::with_bundle_identifier(bundle_identifier),
+                ),
+                None => {
+                    let pid = context.notification.app.pid;
+                    let application = RunningApplication::with_process_identifier(pid);
+                    (AppKey::Pid(pid), application.into_iter().collect())
+                }
+            };
+
+            if applications.is_empty() {
+                Err(ActionError::NotRunning(key))
+            } else {
+                Ok((key, applications))
+            }
+        };
+
+        let for_each = |bundle_identifier: &Option<String>,
+                        request: fn(&RunningApplication) -> bool| {
+            let (key, applications) = applications(bundle_identifier)?;
+            // Make the request to every instance, even if one of them refuses.
+            let accepted: Vec<bool> = applications.iter().map(request).collect();
+
+            if accepted.contains(&false) {
+                Err(ActionError::Refused(key))
+            } else {
+                Ok(())
+            }
+        };
+
+        match action {
+            Action::Shell { command } => {
+                let mut child = std::process::Command::new("/bin/sh")
+                    .arg("-c")
+                    .arg(command)
+                    .envs(context.environment())
+                    .stdin(std::process::Stdio::null())
+                    .spawn()
+                    .map_err(ActionError::Spawn)?;
+
+                // Reap the command once it exits.
+                std::thread::spawn(move || child.wait());
+                Ok(())
+            }
+            Action::Hide { bundle_identifier } => for_each(bundle_identifier, |app| app.hide()),
+            Action::Unhide { bundle_identifier } => for_each(bundle_identifier, |app| app.unhide()),
+            Action::Activate { bundle_identifier } => {
+                for_each(bundle_identifier, |app| app.activate())
+            }
+            Action::Notify { name, distributed } => {
+                let user_info = context.user_info();
+
+                if *distributed {
+                    unsafe { DistributedNotificationCenter::default_center() }.post(
+                        name,
+                        None,
+                        Some(&user_info),
+                        false,
+                    );
+                } else {
+                    unsafe { NotificationCenter::default_center() }
+                        .ok_or(ActionError::NoNotificationCenter)?
+                        .post_name_with_plist(name, None, &user_info);
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Matches notifications against a [`RuleSet`] and runs the actions of the matching rules.
+///
+/// Each notification is applied to the engine's [`AppRegistry`] before the rules are matched, so
+/// conditions see the application the notification is about in its new state. The actions of
+/// every matching rule are run in order; a failing action doesn't stop the ones after it.
+///
+/// As an [`EventSink`], failures are passed to the [`on_failure`](Self::on_failure) handler, if
+/// any.
+pub struct RuleEngine<E> {
+    rules: RuleSet,
+    registry: AppRegistry,
+    executor: E,
+    on_failure: Option<Box<dyn FnMut(ActionFailure) + Send>>,
+}
+
+impl<E: ActionExecutor> RuleEngine<E> {
+    pub fn new(rules: RuleSet, executor: E) -> RuleEngine<E> {
+        RuleEngine {
+            rules,
+            registry: AppRegistry::new(),
+            executor,
+            on_failure: None,
+        }
+    }
+
+    /// Passes the actions that fail while the engine is used as an [`EventSink`] to `handler`.
+    pub fn on_failure<F>(mut self, handler: F) -> RuleEngine<E>
+    where
+        F: FnMut(ActionFailure) + Send + 'static,
+    {
+        self.on_failure = Some(Box::new(handler));
+        self
+    }
+
+    /// Applies `notification` and runs the actions of the rules it matches, returning the
+    /// actions that failed.
+    pub fn handle(&mut self, notification: &AppNotification) -> Vec<ActionFailure> {
+        self.registry.apply(notification);
+        self.dispatch(notification)
+    }
+
+    /// Like [`handle`](Self::handle), for an [`AppEvent`].
+    pub fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        stamp: Option<EventStamp>,
+    ) -> Vec<ActionFailure> {
+        self.registry.apply_event(event);
+
+        let mut notification = AppNotification::from(event.clone());
+        notification.stamp = stamp;
+        self.dispatch(&notification)
+    }
+
+    fn dispatch(&mut self, notification: &AppNotification) -> Vec<ActionFailure> {
+        let mut failures = Vec::new();
+
+        for rule in self.rules.matching(notification, &self.registry) {
+            let context = ActionContext { rule, notification };
+
+            for action in &rule.actions {
+                if let Err(error) = self.executor.execute(action, &context) {
+                    failures.push(ActionFailure {
+                        rule: rule.name.clone(),
+                        action: action.clone(),
+                        error,
+                    });
+                }
+            }
+        }
+
+        failures
+    }
+
+    fn report(&mut self, failures: Vec<ActionFailure>) {
+        if let Some(on_failure) = &mut self.on_failure {
+            failures.into_iter().for_each(on_failure);
+        }
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn registry(&self) -> &AppRegistry {
+        &self.registry
+    }
+
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    pub fn executor_mut(&mut self) -> &mut E {
+        &mut self.executor
+    }
+}
+
+impl<E> EventSink for RuleEngine<E>
+where
+    E: ActionExecutor + Send + 'static,
+{
+    fn send(&mut self, notification: AppNotification) -> Result<(), Disconnected> {
+        let failures = self.handle(&notification);
+        self.report(failures);
+        Ok(())
+    }
+
+    fn send_event(&mut self, event: AppEvent, stamp: EventStamp) -> Result<(), Disconnected> {
+        let failures = self.handle_event(&event, Some(stamp));
+        self.report(failures);
+        Ok(())
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for RuleEngine<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuleEngine")
+            .field("rules", &self.rules)
+            .field("registry", &self.registry)
+            .field("executor", &self.executor)
+            .field("on_failure", &self.on_failure.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::RunningAppInfo;
+
+    fn app(pid: usize, bundle_identifier: &str, localized_name: &str) -> RunningAppInfo {
+        RunningAppInfo {
+            pid,
+            localized_name: Some(localized_name.to_string()),
+            bundle_identifier: Some(bundle_identifier.to_string()),
+            bundle_url: None,
+        }
+    }
+
+    fn notification(notification_type: NotificationType, app: RunningAppInfo) -> AppNotification {
+        AppNotification {
+            notification_type,
+            app,
+            stamp: None,
+        }
+    }
+
+    fn xcode() -> RunningAppInfo {
+        app(42, "com.apple.dt.Xcode", "Xcode")
+    }
+
+    fn safari() -> RunningAppInfo {
+        app(43, "com.apple.Safari", "Safari")
+    }
+
+    fn shell(command: &str) -> Action {
+        Action::Shell {
+            command: command.to_string(),
+        }
+    }
+
+    /// The names of the rules whose actions `engine` ran, and forgets them.
+    fn ran(engine: &mut RuleEngine<RecordingExecutor>) -> Vec<String> {
+        let mut names: Vec<String> = engine
+            .executor_mut()
+            .take()
+            .into_iter()
+            .map(|executed| executed.rule)
+            .collect();
+        names.dedup();
+        names
+    }
+
+    #[test]
+    fn parses_rules() {
+        let rules = RuleSet::from_toml(
+            r#"
+            [[rule]]
+            name = "everything"
+            on = ["activated", "already_running"]
+            bundle_identifiers = ["com.apple.Safari"]
+            name_patterns = ["*code"]
+            actions = [
+                { type = "shell", command = "true" },
+                { type = "hide" },
+                { type = "unhide", bundle_identifier = "com.apple.Safari" },
+                { type = "activate" },
+                { type = "notify", name = "Done", distributed = true },
+            ]
+
+            [rule.when]
+            running = ["a"]
+            not_running = ["b"]
+            hidden = ["c"]
+            visible = ["d"]
+            frontmost = "e"
+
+            [[rule]]
+            name = "defaults"
+            actions = []
+            "#,
+        )
+        .unwrap();
+
+        let everything = &rules.rules[0];
+        assert_eq!(
+            everything.notification_types,
+            [
+                NotificationType::Activated,
+                NotificationType::AlreadyRunning
+            ]
+        );
+        assert_eq!(everything.bundle_identifiers, ["com.apple.Safari"]);
+        assert_eq!(everything.name_patterns, ["*code"]);
+        assert_eq!(
+            everything.when,
+            Conditions {
+                running: vec!["a".to_string()],
+                not_running: vec!["b".to_string()],
+                hidden: vec!["c".to_string()],
+                visible: vec!["d".to_string()],
+                frontmost: Some("e".to_string()),
+            }
+        );
+        assert_eq!(
+            everything.actions,
+            [
+                shell("true"),
+                Action::Hide {
+                    bundle_identifier: None
+                },
+                Action::Unhide {
+                    bundle_identifier: Some("com.apple.Safari".to_string())
+                },
+                Action::Activate {
+                    bundle_identifier: None
+                },
+                Action::Notify {
+                    name: "Done".to_string(),
+                    distributed: true
+                },
+            ]
+        );
+
+        let defaults = &rules.rules[1];
+        assert!(defaults.notification_types.is_empty());
+        assert_eq!(defaults.when, Conditions::default());
+
+        assert_eq!(RuleSet::from_toml("").unwrap(), RuleSet::default());
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for toml in [
+            // Unknown fields, at every level.
+            "title = \"rules\"",
+            "[[rule]]\nname = \"a\"\nactions = []\npriority = 1",
+            "[[rule]]\nname = \"a\"\nactions = []\nwhen = { launched = [\"b\"] }",
+            "[[rule]]\nname = \"a\"\nactions = [{ type = \"hide\", pid = 1 }]",
+            // Unknown action and notification types.
+            "[[rule]]\nname = \"a\"\nactions = [{ type = \"quit\" }]",
+            "[[rule]]\nname = \"a\"\non = [\"opened\"]\nactions = []",
+            // Missing fields.
+            "[[rule]]\nactions = []",
+            "[[rule]]\nname = \"a\"",
+            "[[rule]]\nname = \"a\"\nactions = [{ type = \"shell\" }]",
+        ] {
+            assert!(
+                matches!(RuleSet::from_toml(toml), Err(RulesError::Toml(_))),
+                "{toml}"
+            );
+        }
+    }
+
+    #[test]
+    fn triggers() {
+        let rule = |toml: &str| -> Rule {
+            RuleSet::from_toml(&format!("[[rule]]\nname = \"a\"\nactions = []\n{toml}"))
+                .unwrap()
+                .rules
+                .remove(0)
+        };
+
+        let any = rule("");
+        assert!(any.triggered_by(&notification(NotificationType::Hidden, xcode())));
+
+        let on = rule("on = [\"launched\", \"terminated\"]");
+        assert!(on.triggered_by(&notification(NotificationType::Launched, xcode())));
+        assert!(on.triggered_by(&notification(NotificationType::Terminated, safari())));
+        assert!(!on.triggered_by(&notification(NotificationType::Activated, xcode())));
+
+        let bundle_identifiers = rule("bundle_identifiers = [\"com.apple.Safari\"]");
+        assert!(bundle_identifiers.triggered_by(&notification(NotificationType::Hidden, safari())));
+        assert!(!bundle_identifiers.triggered_by(&notification(NotificationType::Hidden, xcode())));
+
+        // Bundle identifiers and name patterns match either.
+        let either = rule("bundle_identifiers = [\"com.apple.Safari\"]\nname_patterns = [\"X*E\"]");
+        assert!(either.triggered_by(&notification(NotificationType::Hidden, safari())));
+        assert!(either.triggered_by(&notification(NotificationType::Hidden, xcode())));
+        assert!(!either.triggered_by(&notification(
+            NotificationType::Hidden,
+            app(44, "com.apple.mail", "Mail")
+        )));
+    }
+
+    #[test]
+    fn conditions_see_registry_after_apply() {
+        let rules = RuleSet::from_toml(
+            r#"
+            [[rule]]
+            name = "running"
+            when = { running = ["com.apple.Safari"] }
+            actions = [{ type = "shell", command = "true" }]
+
+            [[rule]]
+            name = "not_running"
+            when = { not_running = ["com.apple.Safari"] }
+            actions = [{ type = "shell", command = "true" }]
+
+            [[rule]]
+            name = "hidden"
+            when = { hidden = ["com.apple.Safari"] }
+            actions = [{ type = "shell", command = "true" }]
+
+            [[rule]]
+            name = "visible"
+            when = { visible = ["com.apple.Safari"] }
+            actions = [{ type = "shell", command = "true" }]
+
+            [[rule]]
+            name = "frontmost"
+            when = { frontmost = "com.apple.Safari" }
+            actions = [{ type = "shell", command = "true" }]
+            "#,
+        )
+        .unwrap();
+        let mut engine = RuleEngine::new(rules, RecordingExecutor::new());
+
+        engine.handle(&notification(NotificationType::Launched, xcode()));
+        assert_eq!(ran(&mut engine), ["not_running"]);
+
+        // The notification itself is applied first, so Safari is already running and frontmost.
+        engine.handle(&notification(NotificationType::Activated, safari()));
+        assert_eq!(ran(&mut engine), ["running", "visible", "frontmost"]);
+
+        engine.handle(&notification(NotificationType::Hidden, safari()));
+        assert_eq!(ran(&mut engine), ["running", "hidden", "frontmost"]);
+
+        engine.handle(&notification(NotificationType::Activated, xcode()));
+        assert_eq!(ran(&mut engine), ["running", "hidden"]);
+
+        engine.handle(&notification(NotificationType::Terminated, safari()));
+        assert_eq!(ran(&mut engine), ["not_running"]);
+    }
+
+    #[test]
+    fn runs_matching_rules_in_order() {
+        let rules = RuleSet::from_toml(
+            r#"
+            [[rule]]
+            name = "first"
+            actions = [{ type = "shell", command = "1" }, { type = "shell", command = "2" }]
+
+            [[rule]]
+            name = "skipped"
+            on = ["terminated"]
+            actions = [{ type = "shell", command = "3" }]
+
+            [[rule]]
+            name = "second"
+            actions = [{ type = "shell", command = "4" }]
+            "#,
+        )
+        .unwrap();
+        let mut engine = RuleEngine::new(rules, RecordingExecutor::new());
+
+        let activated = notification(NotificationType::Activated, xcode());
+        assert!(engine.handle(&activated).is_empty());
+
+        let executed = engine.executor_mut().take();
+        assert_eq!(
+            executed,
+            [
+                ExecutedAction {
+                    rule: "first".to_string(),
+                    action: shell("1"),
+                    notification: activated.clone(),
+                },
+                ExecutedAction {
+                    rule: "first".to_string(),
+                    action: shell("2"),
+                    notification: activated.clone(),
+                },
+                ExecutedAction {
+                    rule: "second".to_string(),
+                    action: shell("4"),
+                    notification: activated,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn failing_action_does_not_stop_later_ones() {
+        let rules = RuleSet::from_toml(
+            r#"
+            [[rule]]
+            name = "a"
+            actions = [{ type = "shell", command = "fail" }, { type = "shell", command = "ok" }]
+
+            [[rule]]
+            name = "b"
+            actions = [{ type = "shell", command = "ok" }]
+            "#,
+        )
+        .unwrap();
+
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let executor = {
+            let ran = ran.clone();
+            move |action: &Action, context: &ActionContext<'_>| {
+                ran.lock()
+                    .unwrap()
+                    .push((context.rule.name.clone(), action.clone()));
+
+                match action {
+                    Action::Shell { command } if command == "fail" => Err(ActionError::NotRunning(
+                        AppKey::Pid(context.notification.app.pid),
+                    )),
+                    _ => Ok(()),
+                }
+            }
+        };
+
+        let mut engine = RuleEngine::new(rules, executor);
+        let failures = engine.handle(&notification(NotificationType::Activated, xcode()));
+
+        assert_eq!(ran.lock().unwrap().len(), 3);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].rule, "a");
+        assert_eq!(failures[0].action, shell("fail"));
+        assert!(matches!(
+            failures[0].error,
+            ActionError::NotRunning(AppKey::Pid(42))
+        ));
+        assert_eq!(
+            failures[0].to_string(),
+            "rule \"a\" failed: pid 42 isn't running"
+        );
+
+        // As an event sink, failures go to the handler.
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = engine.on_failure({
+            let reported = reported.clone();
+            move |failure| reported.lock().unwrap().push(failure.rule)
+        });
+
+        engine
+            .send(notification(NotificationType::Activated, safari()))
+            .unwrap();
+        engine
+            .send_event(
+                AppEvent::Hidden { app: safari() },
+                crate::EventStamp::now(0),
+            )
+            .unwrap();
+        assert_eq!(*reported.lock().unwrap(), ["a", "a"]);
+    }
+
+    #[test]
+    fn context_environment_and_user_info() {
+        let rule = Rule {
+            name: "log".to_string(),
+            notification_types: Vec::new(),
+            bundle_identifiers: Vec::new(),
+            name_patterns: Vec::new(),
+            when: Conditions::default(),
+            actions: Vec::new(),
+        };
+
+        let mut full = notification(NotificationType::Activated, xcode());
+        full.app.bundle_url = Some("file:///Applications/Xcode.app/".to_string());
+        full.stamp = Some(EventStamp {
+            sequence: 7,
+            ..EventStamp::now(0)
+        });
+
+        let context = ActionContext {
+            rule: &rule,
+            notification: &full,
+        };
+        assert_eq!(
+            context.environment(),
+            [
+                ("COCOA_UTILS_RULE", "log".to_string()),
+                ("COCOA_UTILS_NOTIFICATION_TYPE", "activated".to_string()),
+                ("COCOA_UTILS_PID", "42".to_string()),
+                (
+                    "COCOA_UTILS_BUNDLE_IDENTIFIER",
+                    "com.apple.dt.Xcode".to_string()
+                ),
+                ("COCOA_UTILS_LOCALIZED_NAME", "Xcode".to_string()),
+                (
+                    "COCOA_UTILS_BUNDLE_PATH",
+                    "/Applications/Xcode.app".to_string()
+                ),
+                ("COCOA_UTILS_SEQUENCE", "7".to_string()),
+            ]
+        );
+
+        let user_info = context.user_info();
+        let keys: Vec<&str> = user_info.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            [
+                "bundle_identifier",
+                "bundle_path",
+                "localized_name",
+                "notification_type",
+                "pid",
+                "rule",
+                "sequence",
+            ]
+        );
+        assert_eq!(
+            user_info.get("pid"),
+            Some(&plist::Value::String("42".to_string()))
+        );
+
+        // Missing details are left out.
+        let bare = notification(
+            NotificationType::Terminated,
+            RunningAppInfo {
+                pid: 7,
+                localized_name: None,
+                bundle_identifier: None,
+                bundle_url: None,
+            },
+        );
+        let context = ActionContext {
+            rule: &rule,
+            notification: &bare,
+        };
+        let names: Vec<&str> = context
+            .environment()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "COCOA_UTILS_RULE",
+                "COCOA_UTILS_NOTIFICATION_TYPE",
+                "COCOA_UTILS_PID"
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+/// When a watcher received a notification, and where it falls in the watcher's sequence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventStamp {
+    /// Counts the notifications a watcher receives, from 0. Notifications that are dropped while
+    /// the watcher is paused still use up a number.
+    pub sequence: u64,
+    /// The wall-clock time the notification was received.
+    pub time: SystemTime,
+    /// A monotonic clock reading, relative to a point fixed for the life of the process, so it's
+    /// comparable across watchers but not across processes.
+    pub monotonic: Duration,
+}
+
+impl EventStamp {
+    /// Stamps a notification received now.
+    pub fn now(sequence: u64) -> EventStamp {
+        EventStamp {
+            sequence,
+            time: SystemTime::now(),
+            monotonic: monotonic_now(),
+        }
+    }
+}
+
+fn monotonic_now() -> Duration {
+    static ANCHOR: OnceLock<Instant> = OnceLock::new();
+    ANCHOR.get_or_init(Instant::now).elapsed()
+}
+
+/// How a stamp relates to the ones seen before it, see [`SequenceCheck`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SequenceStatus {
+    /// The stamp directly follows the previous one, or is the first one seen.
+    InOrder,
+    /// `missed` notifications were skipped since the previous stamp.
+    Gap { missed: u64 },
+    /// The stamp doesn't come after the previous one: it was reordered or delivered twice.
+    Stale,
+}
+
+/// Tracks the sequence numbers of one watcher's notifications to detect drops and reordering.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SequenceCheck {
+    last: Option<u64>,
+}
+
+impl SequenceCheck {
+    pub fn new() -> SequenceCheck {
+        SequenceCheck::default()
+    }
+
+    /// Checks `stamp` against the newest stamp seen so far, which it replaces unless it's stale.
+    pub fn check(&mut self, stamp: &EventStamp) -> SequenceStatus {
+        let status = match self.last {
+            None => SequenceStatus::InOrder,
+            Some(last) if stamp.sequence <= last => return SequenceStatus::Stale,
+            Some(last) if stamp.sequence == last + 1 => SequenceStatus::InOrder,
+            Some(last) => SequenceStatus::Gap {
+                missed: stamp.sequence - last - 1,
+            },
+        };
+
+        self.last = Some(stamp.sequence);
+        status
+    }
+}
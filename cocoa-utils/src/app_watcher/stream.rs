@@ -0,0 +1,58 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc;
+use futures::{Stream, StreamExt};
+
+use crate::{AppNotification, AppWatcherHandle, NSAppWatcher};
+
+/// The number of notifications [`stream`] buffers before the watcher waits for the consumer.
+pub const DEFAULT_STREAM_CAPACITY: usize = 64;
+
+/// An asynchronous stream of application notifications, returned by [`stream`].
+///
+/// The watcher runs on its own thread and is stopped when the stream is dropped.
+pub struct AppNotificationStream {
+    receiver: mpsc::Receiver<AppNotification>,
+    handle: AppWatcherHandle,
+}
+
+impl AppNotificationStream {
+    /// Returns the handle of the watcher feeding this stream, e.g. to pause it.
+    pub fn handle(&self) -> &AppWatcherHandle {
+        &self.handle
+    }
+}
+
+impl Stream for AppNotificationStream {
+    type Item = AppNotification;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<AppNotification>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for AppNotificationStream {
+    fn drop(&mut self) {
+        self.handle.stop();
+    }
+}
+
+/// Watches the shared workspace's application notifications on a dedicated thread, and returns
+/// them as a `Stream` that works with any executor.
+///
+/// Up to [`DEFAULT_STREAM_CAPACITY`] notifications are buffered.
+pub fn stream() -> AppNotificationStream {
+    stream_with_capacity(DEFAULT_STREAM_CAPACITY)
+}
+
+/// Like [`stream`], but buffers up to `capacity` notifications.
+///
+/// Once the buffer is full, the watcher thread waits for the consumer to catch up instead of
+/// dropping notifications.
+pub fn stream_with_capacity(capacity: usize) -> AppNotificationStream {
+    let (sender, receiver) = mpsc::channel(capacity);
+    let handle = NSAppWatcher::spawn(sender);
+
+    AppNotificationStream { receiver, handle }
+}
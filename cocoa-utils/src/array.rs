@@ -0,0 +1,68 @@
+use crate::plist;
+use crate::prelude::*;
+
+/// Rust wrapper around an `NSArray`.
+#[derive(CocoaType)]
+pub struct Array {
+    ptr: Id,
+}
+
+impl Array {
+    /// Returns the number of objects in the array.
+    pub fn len(&self) -> usize {
+        unsafe { msg_send![self.ptr, count] }
+    }
+
+    /// Returns `true` if the array contains no objects.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the object at `index`, or `None` if `index` is out of bounds.
+    pub fn get_id(&self, index: usize) -> Option<Id> {
+        if index >= self.len() {
+            return None;
+        }
+
+        unsafe { Some(msg_send![self.ptr, objectAtIndex: index]) }
+    }
+
+    /// Returns an iterator over the objects in the array.
+    pub fn iter(&self) -> impl Iterator<Item = Id> + '_ {
+        (0..self.len()).map(move |index| unsafe { msg_send![self.ptr, objectAtIndex: index] })
+    }
+
+    /// Converts the array to property list values.
+    ///
+    /// Returns `None` if the array contains anything that isn't a property list type.
+    pub fn to_plist(&self) -> Option<Vec<plist::Value>> {
+        self.iter()
+            .map(|item| unsafe { plist::Value::from_id(item) })
+            .collect()
+    }
+
+    /// Creates an autoreleased `NSArray` from property list values.
+    pub fn from_plist(values: &[plist::Value]) -> Array {
+        unsafe { Array::from_ptr(plist::Value::Array(values.to_vec()).to_id()).unwrap() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Array {
+    /// Fails if the array contains anything that isn't a property list type.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+
+        self.to_plist()
+            .ok_or_else(|| S::Error::custom("NSArray contains a non-property list object"))?
+            .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Array {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<plist::Value>::deserialize(deserializer)?;
+        Ok(Array::from_plist(&values))
+    }
+}
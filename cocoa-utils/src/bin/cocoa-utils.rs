@@ -0,0 +1,53 @@
+use std::process::ExitCode;
+
+use cocoa_utils::cli::{self, Command, WatchOptions};
+
+fn main() -> ExitCode {
+    let args = std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned());
+
+    let code = match Command::parse(args) {
+        Ok(Command::Watch(options)) => watch(options),
+        Ok(Command::Help) => {
+            print!("{}", cli::USAGE);
+            cli::EXIT_SUCCESS
+        }
+        Ok(Command::Version) => {
+            println!("cocoa-utils {}", env!("CARGO_PKG_VERSION"));
+            cli::EXIT_SUCCESS
+        }
+        Err(error) => {
+            eprintln!("cocoa-utils: {error}");
+            eprintln!("Run `cocoa-utils --help` for usage.");
+            cli::EXIT_USAGE
+        }
+    };
+
+    ExitCode::from(code)
+}
+
+#[cfg(target_os = "macos")]
+fn watch(options: WatchOptions) -> u8 {
+    use cocoa_utils::cli::{Outcome, Watch};
+
+    let (sender, receiver) = crossbeam::channel::unbounded();
+    let handle = options.builder().spawn(sender);
+
+    let outcome = Watch::new(options, std::io::stdout().lock()).run(&receiver);
+    handle.stop();
+
+    match &outcome {
+        Outcome::Stopped => eprintln!("cocoa-utils: the watcher stopped unexpectedly"),
+        Outcome::Failed(error) => eprintln!("cocoa-utils: failed to write the output: {error}"),
+        _ => {}
+    }
+
+    outcome.exit_code()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn watch(_options: WatchOptions) -> u8 {
+    eprintln!("cocoa-utils: watching requires macOS");
+    cli::EXIT_FAILURE
+}
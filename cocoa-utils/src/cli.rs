@@ -0,0 +1,787 @@
+//! The library side of the `cocoa-utils` command-line tool: argument parsing, filtering and
+//! output formatting, which don't need a running watcher.
+//!
+//! ```
+//! use cocoa_utils::cli::{Command, Outcome, Watch};
+//! use cocoa_utils::{AppNotification, NotificationType, RunningAppInfo};
+//!
+//! let Command::Watch(options) = Command::parse(["watch", "--format", "logfmt", "--count", "1"])
+//!     .unwrap()
+//! else {
+//!     unreachable!()
+//! };
+//!
+//! let (sender, receiver) = crossbeam::channel::unbounded();
+//! sender
+//!     .send(AppNotification::new(
+//!         NotificationType::Activated,
+//!         RunningAppInfo {
+//!             pid: 42,
+//!             localized_name: Some("Safari".to_string()),
+//!             bundle_identifier: Some("com.apple.Safari".to_string()),
+//!             bundle_url: None,
+//!         },
+//!     ))
+//!     .unwrap();
+//!
+//! let mut output = Vec::new();
+//! let outcome = Watch::new(options, &mut output).run(&receiver);
+//!
+//! assert!(matches!(outcome, Outcome::Done));
+//! assert_eq!(
+//!     String::from_utf8(output).unwrap(),
+//!     "type=activated pid=42 name=Safari bundle_id=com.apple.Safari\n"
+//! );
+//! ```
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crossbeam::channel::{Receiver, RecvTimeoutError};
+
+use crate::{AppFilter, AppNotification, NotificationType};
+
+/// `--count` notifications were written, or the timeout elapsed without a count to reach.
+pub const EXIT_SUCCESS: u8 = 0;
+/// The timeout elapsed before `--count` notifications were written.
+pub const EXIT_TIMED_OUT: u8 = 1;
+/// The arguments are invalid.
+pub const EXIT_USAGE: u8 = 2;
+/// The watcher stopped, or the output couldn't be written.
+pub const EXIT_FAILURE: u8 = 3;
+
+pub const USAGE: &str = "\
+Usage: cocoa-utils watch [OPTIONS]
+
+Prints the application notifications of the user's session as they're posted.
+
+Options:
+  -t, --types <TYPES>      Only report these comma-separated notification types: launching,
+                           launched, terminated, hidden, unhidden, activated, deactivated,
+                           already_running (which implies --snapshot)
+  -b, --bundle-id <ID>     Only report the application with this bundle identifier
+  -n, --name <PATTERN>     Only report applications whose name matches this pattern, where `*`
+                           matches any run of characters and `?` a single one, ignoring case
+  -f, --format <FORMAT>    human (the default), jsonl or logfmt
+  -c, --count <N>          Exit after reporting N notifications
+      --timeout <SECONDS>  Exit after SECONDS, which may be fractional
+  -s, --snapshot           First report the applications that are already running
+  -h, --help               Print this help
+  -V, --version            Print the version
+
+--bundle-id and --name can be repeated; an application is reported if it matches any of them.
+
+Exit status:
+  0  --count notifications were reported, or --timeout elapsed without --count
+  1  --timeout elapsed before --count notifications were reported
+  2  the arguments are invalid
+  3  the watcher stopped, or the output couldn't be written
+";
+
+/// A command line, without the program name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Watch(WatchOptions),
+    Help,
+    Version,
+}
+
+impl Command {
+    pub fn parse<I>(args: I) -> Result<Command, UsageError>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+
+        match args.next().as_deref() {
+            Some("watch") => WatchOptions::parse(args),
+            Some("help" | "-h" | "--help") | None => Ok(Command::Help),
+            Some("-V" | "--version") => Ok(Command::Version),
+            Some(command) => Err(UsageError(format!("unknown command `{command}`"))),
+        }
+    }
+}
+
+/// Invalid command-line arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// How [`Watch`] writes each notification, on a line of its own.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// `activated       Safari (pid 42, com.apple.Safari)`
+    #[default]
+    Human,
+    /// The notification serialized as JSON.
+    JsonLines,
+    /// `type=activated pid=42 name=Safari bundle_id=com.apple.Safari`, preceded by `time` and
+    /// `seq` when the notification is stamped.
+    Logfmt,
+}
+
+impl FromStr for OutputFormat {
+    type Err = UsageError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "human" => Ok(OutputFormat::Human),
+            "jsonl" | "json" => Ok(OutputFormat::JsonLines),
+            "logfmt" => Ok(OutputFormat::Logfmt),
+            _ => Err(UsageError(format!(
+                "unknown format `{format}`, expected human, jsonl or logfmt"
+            ))),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Formats `notification` as a line, without the line break.
+    pub fn format(&self, notification: &AppNotification) -> String {
+        match self {
+            OutputFormat::Human => format_human(notification),
+            OutputFormat::JsonLines => {
+                serde_json::to_string(notification).expect("notifications always serialize to JSON")
+            }
+            OutputFormat::Logfmt => format_logfmt(notification),
+        }
+    }
+}
+
+fn format_human(notification: &AppNotification) -> String {
+    let app = &notification.app;
+    let name = app
+        .localized_name
+        .as_deref()
+        .or(app.bundle_identifier.as_deref())
+        .unwrap_or("<unnamed>");
+
+    let mut line = format!(
+        "{:<15} {name} (pid {}",
+        notification.notification_type.as_str(),
+        app.pid
+    );
+
+    if let Some(bundle_identifier) = &app.bundle_identifier {
+        let _ = write!(line, ", {bundle_identifier}");
+    }
+
+    line.push(')');
+    line
+}
+
+fn format_logfmt(notification: &AppNotification) -> String {
+    let app = &notification.app;
+    let mut pairs = Vec::new();
+
+    if let Some(stamp) = &notification.stamp {
+        let time = match stamp.time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs_f64(),
+            Err(error) => -error.duration().as_secs_f64(),
+        };
+
+        pairs.push(("time", format!("{time:.3}")));
+        pairs.push(("seq", stamp.sequence.to_string()));
+    }
+
+    pairs.push(("type", notification.notification_type.as_str().to_string()));
+    pairs.push(("pid", app.pid.to_string()));
+
+    if let Some(localized_name) = &app.localized_name {
+        pairs.push(("name", localized_name.clone()));
+    }
+    if let Some(bundle_identifier) = &app.bundle_identifier {
+        pairs.push(("bundle_id", bundle_identifier.clone()));
+    }
+    if let Some(bundle_path) = app.bundle_path() {
+        pairs.push(("path", bundle_path.to_string_lossy().into_owned()));
+    }
+
+    let mut line = String::new();
+
+    for (key, value) in pairs {
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.push_str(key);
+        line.push('=');
+        push_logfmt_value(&mut line, &value);
+    }
+
+    line
+}
+
+/// Quotes `value` if it's empty or contains spaces, quotes, `=` or control characters.
+fn push_logfmt_value(line: &mut String, value: &str) {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '"' || c == '=' || c == '\\' || c.is_control());
+
+    if !needs_quotes {
+        line.push_str(value);
+        return;
+    }
+
+    line.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(line, "\\u{{{:x}}}", c as u32);
+            }
+            c => line.push(c),
+        }
+    }
+
+    line.push('"');
+}
+
+/// The options of the `watch` command.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchOptions {
+    /// The types to report; empty to report every type.
+    pub notification_types: Vec<NotificationType>,
+    pub filter: AppFilter,
+    pub format: OutputFormat,
+    pub count: Option<usize>,
+    pub timeout: Option<Duration>,
+    pub snapshot: bool,
+}
+
+impl WatchOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Command, UsageError> {
+        let mut options = WatchOptions::default();
+
+        while let Some(arg) = args.next() {
+            // Accept both `--option value` and `--option=value`.
+            let (name, mut inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .take()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| UsageError(format!("`{name}` requires a value")))
+            };
+
+            match name.as_str() {
+                "-t" | "--types" => {
+                    for notification_type in value()?.split(',').map(str::trim) {
+                        options
+                            .notification_types
+                            .push(parse_notification_type(notification_type)?);
+                    }
+                }
+                "-b" | "--bundle-id" => options.filter = options.filter.bundle_identifier(value()?),
+                "-n" | "--name" => options.filter = options.filter.name_pattern(value()?),
+                "-f" | "--format" => options.format = value()?.parse()?,
+                "-c" | "--count" => {
+                    let count = value()?;
+                    options.count = Some(count.parse().map_err(|_| {
+                        UsageError(format!("invalid count `{count}`, expected a number"))
+                    })?);
+                }
+                "--timeout" => {
+                    let timeout = value()?;
+                    let seconds: f64 = timeout.parse().map_err(|_| {
+                        UsageError(format!("invalid timeout `{timeout}`, expected seconds"))
+                    })?;
+                    options.timeout = Some(Duration::try_from_secs_f64(seconds).map_err(|_| {
+                        UsageError(format!("invalid timeout `{timeout}`, expected seconds"))
+                    })?);
+                }
+                "-s" | "--snapshot" => options.snapshot = true,
+                "-h" | "--help" => return Ok(Command::Help),
+                "-V" | "--version" => return Ok(Command::Version),
+                _ => return Err(UsageError(format!("unexpected argument `{name}`"))),
+            }
+
+            if inline_value.is_some() {
+                return Err(UsageError(format!("`{name}` doesn't take a value")));
+            }
+        }
+
+        // `already_running` notifications are only ever posted by a snapshot.
+        if options
+            .notification_types
+            .contains(&NotificationType::AlreadyRunning)
+        {
+            options.snapshot = true;
+        }
+
+        Ok(Command::Watch(options))
+    }
+
+    /// Returns `true` if `notification` should be reported.
+    ///
+    /// With [`snapshot`](Self::snapshot), `already_running` notifications are reported even if
+    /// they aren't among the selected types.
+    pub fn matches(&self, notification: &AppNotification) -> bool {
+        let selected = self.notification_types.is_empty()
+            || self
+                .notification_types
+                .contains(&notification.notification_type)
+            || (self.snapshot
+                && notification.notification_type == NotificationType::AlreadyRunning);
+
+        selected && self.filter.matches_app(&notification.app)
+    }
+
+    /// Returns a builder for a watcher that only observes the selected notifications.
+    #[cfg(target_os = "macos")]
+    pub fn builder(&self) -> crate::AppWatcherBuilder {
+        crate::NSAppWatcher::builder()
+            .notification_types(self.notification_types.iter().copied())
+            .filter(self.filter.clone())
+            .snapshot(self.snapshot)
+    }
+}
+
+fn parse_notification_type(name: &str) -> Result<NotificationType, UsageError> {
+    NotificationType::ALL
+        .into_iter()
+        .chain([NotificationType::AlreadyRunning])
+        .find(|notification_type| notification_type.as_str() == name)
+        .ok_or_else(|| UsageError(format!("unknown notification type `{name}`")))
+}
+
+/// How a [`Watch`] ended.
+#[derive(Debug)]
+pub enum Outcome {
+    /// `count` notifications were written, or the timeout elapsed without a count to reach.
+    Done,
+    /// The timeout elapsed before `count` notifications were written.
+    TimedOut,
+    /// The watcher stopped before the count was reached or the timeout elapsed.
+    Stopped,
+    /// The reader of the output went away, e.g. `head` exited.
+    OutputClosed,
+    Failed(io::Error),
+}
+
+impl Outcome {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Outcome::Done | Outcome::OutputClosed => EXIT_SUCCESS,
+            Outcome::TimedOut => EXIT_TIMED_OUT,
+            Outcome::Stopped | Outcome::Failed(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+/// Writes the notifications that pass a [`WatchOptions`]' filters until its count or timeout is
+/// reached.
+pub struct Watch<W> {
+    options: WatchOptions,
+    output: W,
+    written: usize,
+}
+
+impl<W: Write> Watch<W> {
+    pub fn new(options: WatchOptions, output: W) -> Watch<W> {
+        Watch {
+            options,
+            output,
+            written: 0,
+        }
+    }
+
+    /// Writes `notification` if it passes the filters, flushing the output so each line is seen
+    /// as soon as it's written. Returns `false` once the count is reached.
+    pub fn handle(&mut self, notification: &AppNotification) -> io::Result<bool> {
+        if self.options.matches(notification) {
+            let line = self.options.format.format(notification);
+            writeln!(self.output, "{line}")?;
+            self.output.flush()?;
+            self.written += 1;
+        }
+
+        Ok(match self.options.count {
+            Some(count) => self.written < count,
+            None => true,
+        })
+    }
+
+    /// Handles the notifications from `receiver` until the count or timeout is reached, or the
+    /// channel disconnects.
+    pub fn run(&mut self, receiver: &Receiver<AppNotification>) -> Outcome {
+        let deadline = self.options.timeout.map(|timeout| Instant::now() + timeout);
+
+        if self.options.count == Some(0) {
+            return Outcome::Done;
+        }
+
+        loop {
+            let received = match deadline {
+                Some(deadline) => receiver.recv_deadline(deadline),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            let notification = match received {
+                Ok(notification) => notification,
+                Err(RecvTimeoutError::Timeout) if self.options.count.is_some() => {
+                    return Outcome::TimedOut
+                }
+                Err(RecvTimeoutError::Timeout) => return Outcome::Done,
+                Err(RecvTimeoutError::Disconnected) => return Outcome::Stopped,
+            };
+
+            match self.handle(&notification) {
+                Ok(true) => {}
+                Ok(false) => return Outcome::Done,
+                Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {
+                    return Outcome::OutputClosed
+                }
+                Err(error) => return Outcome::Failed(error),
+            }
+        }
+    }
+
+    /// The number of notifications written so far.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam::channel;
+
+    use super::*;
+    use crate::{EventStamp, RunningAppInfo};
+
+    fn watch_options<const N: usize>(args: [&str; N]) -> WatchOptions {
+        match Command::parse(["watch"].into_iter().chain(args)) {
+            Ok(Command::Watch(options)) => options,
+            other => panic!("{args:?} parsed to {other:?}"),
+        }
+    }
+
+    fn parse_error<const N: usize>(args: [&str; N]) -> String {
+        Command::parse(["watch"].into_iter().chain(args))
+            .unwrap_err()
+            .0
+    }
+
+    fn safari() -> RunningAppInfo {
+        RunningAppInfo {
+            pid: 42,
+            localized_name: Some("Safari".to_string()),
+            bundle_identifier: Some("com.apple.Safari".to_string()),
+            bundle_url: None,
+        }
+    }
+
+    fn notification(notification_type: NotificationType, app: RunningAppInfo) -> AppNotification {
+        AppNotification {
+            notification_type,
+            app,
+            stamp: None,
+        }
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Command::parse(Vec::<String>::new()), Ok(Command::Help));
+        assert_eq!(Command::parse(["--help"]), Ok(Command::Help));
+        assert_eq!(Command::parse(["-V"]), Ok(Command::Version));
+        assert_eq!(Command::parse(["watch", "-h"]), Ok(Command::Help));
+        assert!(Command::parse(["listen"]).is_err());
+    }
+
+    #[test]
+    fn parses_watch_options() {
+        let options = watch_options([
+            "--types=launched, terminated",
+            "-t",
+            "already_running",
+            "--bundle-id",
+            "com.apple.Safari",
+            "--bundle-id=com.apple.mail",
+            "-n",
+            "x*",
+            "--format=jsonl",
+            "--count=3",
+            "--timeout",
+            "1.5",
+            "-s",
+        ]);
+
+        assert_eq!(
+            options,
+            WatchOptions {
+                notification_types: vec![
+                    NotificationType::Launched,
+                    NotificationType::Terminated,
+                    NotificationType::AlreadyRunning,
+                ],
+                filter: AppFilter::new()
+                    .bundle_identifier("com.apple.Safari")
+                    .bundle_identifier("com.apple.mail")
+                    .name_pattern("x*"),
+                format: OutputFormat::JsonLines,
+                count: Some(3),
+                timeout: Some(Duration::from_millis(1500)),
+                snapshot: true,
+            }
+        );
+
+        assert_eq!(watch_options([]), WatchOptions::default());
+    }
+
+    #[test]
+    fn already_running_implies_snapshot() {
+        let options = watch_options(["-t", "already_running"]);
+
+        assert_eq!(
+            options,
+            WatchOptions {
+                notification_types: vec![NotificationType::AlreadyRunning],
+                snapshot: true,
+                ..WatchOptions::default()
+            }
+        );
+        assert!(options.matches(&notification(NotificationType::AlreadyRunning, safari())));
+        assert!(!options.matches(&notification(NotificationType::Launched, safari())));
+    }
+
+    #[test]
+    fn rejects_invalid_watch_options() {
+        assert_eq!(
+            parse_error(["--snapshot=1"]),
+            "`--snapshot` doesn't take a value"
+        );
+        assert_eq!(
+            parse_error(["--count", "-1"]),
+            "invalid count `-1`, expected a number"
+        );
+        assert_eq!(
+            parse_error(["--count=many"]),
+            "invalid count `many`, expected a number"
+        );
+        assert_eq!(
+            parse_error(["--timeout", "soon"]),
+            "invalid timeout `soon`, expected seconds"
+        );
+        assert_eq!(
+            parse_error(["--timeout=-1"]),
+            "invalid timeout `-1`, expected seconds"
+        );
+        assert_eq!(
+            parse_error(["--timeout", "inf"]),
+            "invalid timeout `inf`, expected seconds"
+        );
+        assert_eq!(parse_error(["--count"]), "`--count` requires a value");
+        assert_eq!(
+            parse_error(["--types", "opened"]),
+            "unknown notification type `opened`"
+        );
+        assert!(parse_error(["--format", "xml"]).starts_with("unknown format `xml`"));
+        assert_eq!(parse_error(["extra"]), "unexpected argument `extra`");
+    }
+
+    #[test]
+    fn formats_human() {
+        let format = |app| OutputFormat::Human.format(&notification(NotificationType::Hidden, app));
+
+        assert_eq!(
+            format(safari()),
+            "hidden          Safari (pid 42, com.apple.Safari)"
+        );
+        assert_eq!(
+            format(RunningAppInfo {
+                localized_name: None,
+                ..safari()
+            }),
+            "hidden          com.apple.Safari (pid 42, com.apple.Safari)"
+        );
+        assert_eq!(
+            format(RunningAppInfo {
+                pid: 7,
+                localized_name: None,
+                bundle_identifier: None,
+                bundle_url: None,
+            }),
+            "hidden          <unnamed> (pid 7)"
+        );
+    }
+
+    #[test]
+    fn formats_logfmt() {
+        let mut stamped = notification(
+            NotificationType::Activated,
+            RunningAppInfo {
+                localized_name: Some("My \"App\"\tv2\u{1}".to_string()),
+                bundle_identifier: Some(String::new()),
+                bundle_url: Some("file:///Applications/My%20App.app/".to_string()),
+                ..safari()
+            },
+        );
+        stamped.stamp = Some(EventStamp {
+            sequence: 3,
+            time: UNIX_EPOCH + Duration::from_millis(1500),
+            monotonic: Duration::ZERO,
+        });
+
+        assert_eq!(
+            OutputFormat::Logfmt.format(&stamped),
+            "time=1.500 seq=3 type=activated pid=42 name=\"My \\\"App\\\"\\tv2\\u{1}\" \
+             bundle_id=\"\" path=\"/Applications/My App.app\""
+        );
+        assert_eq!(
+            OutputFormat::Logfmt.format(&notification(NotificationType::Hidden, safari())),
+            "type=hidden pid=42 name=Safari bundle_id=com.apple.Safari"
+        );
+    }
+
+    #[test]
+    fn formats_json_lines() {
+        let line =
+            OutputFormat::JsonLines.format(&notification(NotificationType::Hidden, safari()));
+
+        assert!(!line.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<AppNotification>(&line).unwrap(),
+            notification(NotificationType::Hidden, safari())
+        );
+    }
+
+    #[test]
+    fn matches_types_and_filter() {
+        let options = watch_options(["--types", "hidden", "--bundle-id", "com.apple.Safari"]);
+        assert!(options.matches(&notification(NotificationType::Hidden, safari())));
+        assert!(!options.matches(&notification(NotificationType::Unhidden, safari())));
+        assert!(!options.matches(&notification(
+            NotificationType::Hidden,
+            RunningAppInfo {
+                bundle_identifier: Some("com.apple.mail".to_string()),
+                ..safari()
+            }
+        )));
+
+        let already_running = notification(NotificationType::AlreadyRunning, safari());
+        assert!(!options.matches(&already_running));
+        assert!(watch_options(["--types", "hidden", "--snapshot"]).matches(&already_running));
+        assert!(watch_options([]).matches(&already_running));
+    }
+
+    /// Runs a watch over `notifications`, keeping the channel connected after them if `keep_open`.
+    fn run<const N: usize>(
+        args: [&str; N],
+        notifications: &[AppNotification],
+        keep_open: bool,
+    ) -> (Outcome, usize) {
+        let (sender, receiver) = channel::unbounded();
+
+        for notification in notifications {
+            sender.send(notification.clone()).unwrap();
+        }
+
+        let _sender = keep_open.then_some(sender);
+        let mut watch = Watch::new(watch_options(args), Vec::new());
+        let outcome = watch.run(&receiver);
+
+        (outcome, watch.written())
+    }
+
+    #[test]
+    fn run_outcomes() {
+        let hidden = notification(NotificationType::Hidden, safari());
+        let unhidden = notification(NotificationType::Unhidden, safari());
+        let both = [hidden.clone(), unhidden.clone()];
+
+        // The count is reached, counting only the notifications that pass the filters.
+        let (outcome, written) = run(["--count", "1", "--types", "unhidden"], &both, true);
+        assert!(matches!(outcome, Outcome::Done));
+        assert_eq!((outcome.exit_code(), written), (EXIT_SUCCESS, 1));
+
+        // The timeout elapses before the count is reached.
+        let (outcome, written) = run(["--count", "3", "--timeout", "0.01"], &both, true);
+        assert!(matches!(outcome, Outcome::TimedOut));
+        assert_eq!((outcome.exit_code(), written), (EXIT_TIMED_OUT, 2));
+
+        // Without a count, the timeout is a normal end.
+        let (outcome, written) = run(["--timeout", "0.01"], &both, true);
+        assert!(matches!(outcome, Outcome::Done));
+        assert_eq!((outcome.exit_code(), written), (EXIT_SUCCESS, 2));
+
+        // The watcher goes away first.
+        let (outcome, written) = run([], &both, false);
+        assert!(matches!(outcome, Outcome::Stopped));
+        assert_eq!((outcome.exit_code(), written), (EXIT_FAILURE, 2));
+
+        let (outcome, written) = run(["--count", "5", "--timeout", "10"], &both, false);
+        assert!(matches!(outcome, Outcome::Stopped));
+        assert_eq!(written, 2);
+
+        // A count of 0 is reached before anything is received.
+        let (outcome, written) = run(["--count", "0"], &both, false);
+        assert!(matches!(outcome, Outcome::Done));
+        assert_eq!(written, 0);
+    }
+
+    /// A writer that fails with `kind`.
+    struct FailingWriter(io::ErrorKind);
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(self.0.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn run_output_errors() {
+        let (sender, receiver) = channel::unbounded();
+        sender
+            .send(notification(NotificationType::Hidden, safari()))
+            .unwrap();
+        sender
+            .send(notification(NotificationType::Hidden, safari()))
+            .unwrap();
+
+        let outcome = Watch::new(
+            WatchOptions::default(),
+            FailingWriter(io::ErrorKind::BrokenPipe),
+        )
+        .run(&receiver);
+        assert!(matches!(outcome, Outcome::OutputClosed));
+        assert_eq!(outcome.exit_code(), EXIT_SUCCESS);
+
+        let outcome = Watch::new(
+            WatchOptions::default(),
+            FailingWriter(io::ErrorKind::PermissionDenied),
+        )
+        .run(&receiver);
+        assert!(matches!(outcome, Outcome::Failed(_)));
+        assert_eq!(outcome.exit_code(), EXIT_FAILURE);
+    }
+}
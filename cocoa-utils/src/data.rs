@@ -0,0 +1,156 @@
+use std::io::Cursor;
+
+use block::ConcreteBlock;
+use cocoa::base::BOOL;
+
+use crate::{ns_error_result, prelude::*};
+
+const WRITING_ATOMIC: NS_uint = 1 << 0;
+const WRITING_WITHOUT_OVERWRITING: NS_uint = 1 << 1;
+
+/// Rust wrapper around an `NSData` with zero-copy access to its bytes.
+#[derive(CocoaType)]
+pub struct Data {
+    ptr: Id,
+}
+
+impl Data {
+    /// Returns the number of bytes in the data object.
+    pub fn len(&self) -> usize {
+        unsafe { msg_send![self.ptr, length] }
+    }
+
+    /// Returns `true` if the data object contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Borrows the contents of the data object without copying them.
+    ///
+    /// The slice borrows from this wrapper, so it can't outlive it. The underlying object must not
+    /// be an `NSMutableData` that is mutated while the slice is alive.
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = self.len();
+
+        if len == 0 {
+            return &[];
+        }
+
+        unsafe {
+            let bytes: *const u8 = msg_send![self.ptr, bytes];
+            std::slice::from_raw_parts(bytes, len)
+        }
+    }
+
+    /// Copies the contents of the data object into a new `Vec<u8>`.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    /// Returns a reader over the contents of the data object.
+    ///
+    /// The reader implements [`std::io::Read`], [`std::io::BufRead`] and [`std::io::Seek`] and
+    /// borrows the bytes without copying them.
+    pub fn reader(&self) -> Cursor<&[u8]> {
+        Cursor::new(self.as_bytes())
+    }
+
+    /// Writes the contents of the data object to `path`.
+    ///
+    /// Unless `overwrite` is set, fails if a file already exists at `path`. Overwriting writes
+    /// atomically through a temporary file.
+    pub fn write_to_file(&self, path: &str, overwrite: bool) -> Result<(), CocoaError> {
+        let options: NS_uint = if overwrite {
+            WRITING_ATOMIC
+        } else {
+            WRITING_WITHOUT_OVERWRITING
+        };
+
+        unsafe {
+            let path = NS_String::from(path);
+
+            ns_error_result("writeToFile:options:error:", |error| {
+                let result: BOOL =
+                    msg_send![self.ptr, writeToFile:path.ptr() options:options error:error];
+                result
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AsRef<[u8]> for Data {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<&[u8]> for Data {
+    /// Allocates a new `NSData` holding a copy of `bytes`.
+    fn from(bytes: &[u8]) -> Self {
+        let ptr: Id = unsafe {
+            let data: Id = msg_send![class!(NSData), alloc];
+            msg_send![data, initWithBytes:bytes.as_ptr() length:bytes.len()]
+        };
+
+        Data { ptr }
+    }
+}
+
+impl From<Vec<u8>> for Data {
+    /// Allocates a new `NSData` that takes ownership of the vector's buffer instead of copying it.
+    ///
+    /// The buffer is freed by Rust once Foundation deallocates the data object.
+    fn from(bytes: Vec<u8>) -> Self {
+        if bytes.is_empty() {
+            return Data::from(&bytes[..]);
+        }
+
+        let len = bytes.len();
+        let bytes = Box::into_raw(bytes.into_boxed_slice());
+
+        let deallocator = ConcreteBlock::new(|ptr: *mut libc::c_void, len: NS_uint| unsafe {
+            let bytes = std::ptr::slice_from_raw_parts_mut(ptr as *mut u8, len as usize);
+            drop(Box::from_raw(bytes));
+        });
+        let deallocator = deallocator.copy();
+
+        let ptr: Id = unsafe {
+            let data: Id = msg_send![class!(NSData), alloc];
+            msg_send![data, initWithBytesNoCopy:bytes as *mut u8
+                                         length:len
+                                    deallocator:&*deallocator]
+        };
+
+        Data { ptr }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Data {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Data {
+    /// Accepts a byte buffer, or a sequence of bytes as formats like JSON encode them.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use crate::plist::Value;
+
+        let bytes = match Value::deserialize(deserializer)? {
+            Value::Data(bytes) => Some(bytes),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| item.as_integer().and_then(|byte| u8::try_from(byte).ok()))
+                .collect(),
+            _ => None,
+        };
+
+        bytes.map(Data::from).ok_or_else(|| {
+            serde::de::Error::custom("expected a byte buffer or a sequence of bytes")
+        })
+    }
+}
@@ -0,0 +1,226 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(target_os = "macos")]
+use crate::prelude::*;
+
+/// Seconds between the Unix epoch and Foundation's reference date, 2001-01-01T00:00:00Z.
+pub const REFERENCE_DATE_UNIX_OFFSET: u64 = 978_307_200;
+
+/// Returns Foundation's reference date, 2001-01-01T00:00:00Z, as a `SystemTime`.
+pub fn reference_date() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(REFERENCE_DATE_UNIX_OFFSET)
+}
+
+/// Converts a time interval relative to the reference date, as used by `NSDate` and binary
+/// property lists, to a `SystemTime`.
+///
+/// Negative intervals are dates before 2001. Returns `None` for non-finite intervals and for
+/// dates that `SystemTime` can't represent.
+pub fn system_time_from_reference_interval(seconds: f64) -> Option<SystemTime> {
+    if seconds >= 0.0 {
+        reference_date().checked_add(Duration::try_from_secs_f64(seconds).ok()?)
+    } else {
+        reference_date().checked_sub(Duration::try_from_secs_f64(-seconds).ok()?)
+    }
+}
+
+/// Converts a `SystemTime` to a time interval relative to the reference date.
+pub fn reference_interval_from_system_time(time: SystemTime) -> f64 {
+    match time.duration_since(reference_date()) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(error) => -error.duration().as_secs_f64(),
+    }
+}
+
+/// Rust wrapper around an `NSDate`.
+#[cfg(target_os = "macos")]
+#[derive(CocoaType)]
+pub struct Date {
+    ptr: Id,
+}
+
+#[cfg(target_os = "macos")]
+impl Date {
+    /// Allocates a new `Date` for the current time.
+    pub fn now() -> Date {
+        Date::from(SystemTime::now())
+    }
+
+    /// Allocates a new `Date` from a time interval relative to the reference date.
+    pub fn from_reference_interval(seconds: f64) -> Date {
+        let ptr: Id = unsafe {
+            let date: Id = msg_send![class!(NSDate), alloc];
+            msg_send![date, initWithTimeIntervalSinceReferenceDate: seconds]
+        };
+
+        Date { ptr }
+    }
+
+    /// Returns the date as a time interval relative to the reference date.
+    pub fn reference_interval(&self) -> f64 {
+        unsafe { msg_send![self.ptr, timeIntervalSinceReferenceDate] }
+    }
+
+    /// Converts the date to a `SystemTime`, or returns `None` if it's outside the range of
+    /// `SystemTime`.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        system_time_from_reference_interval(self.reference_interval())
+    }
+}
+
+/// A [`Date`] is outside the range of the type it's converted to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DateOutOfRange;
+
+impl std::fmt::Display for DateOutOfRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("the date is out of range")
+    }
+}
+
+impl std::error::Error for DateOutOfRange {}
+
+#[cfg(target_os = "macos")]
+impl From<SystemTime> for Date {
+    fn from(time: SystemTime) -> Self {
+        Date::from_reference_interval(reference_interval_from_system_time(time))
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl TryFrom<&Date> for SystemTime {
+    type Error = DateOutOfRange;
+
+    fn try_from(date: &Date) -> Result<Self, Self::Error> {
+        date.to_system_time().ok_or(DateOutOfRange)
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "chrono"))]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Date {
+    fn from(date: chrono::DateTime<Tz>) -> Self {
+        Date::from(SystemTime::from(date))
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "chrono"))]
+impl TryFrom<&Date> for chrono::DateTime<chrono::Utc> {
+    type Error = DateOutOfRange;
+
+    fn try_from(date: &Date) -> Result<Self, Self::Error> {
+        use chrono::TimeZone;
+
+        let nanos = unix_nanos(SystemTime::try_from(date)?);
+        let seconds = i64::try_from(nanos.div_euclid(1_000_000_000)).map_err(|_| DateOutOfRange)?;
+
+        chrono::Utc
+            .timestamp_opt(seconds, nanos.rem_euclid(1_000_000_000) as u32)
+            .single()
+            .ok_or(DateOutOfRange)
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "time"))]
+impl From<time::OffsetDateTime> for Date {
+    fn from(date: time::OffsetDateTime) -> Self {
+        Date::from(SystemTime::from(date))
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "time"))]
+impl TryFrom<&Date> for time::OffsetDateTime {
+    type Error = DateOutOfRange;
+
+    fn try_from(date: &Date) -> Result<Self, Self::Error> {
+        let nanos = unix_nanos(SystemTime::try_from(date)?);
+        time::OffsetDateTime::from_unix_timestamp_nanos(nanos).map_err(|_| DateOutOfRange)
+    }
+}
+
+/// Nanoseconds between the Unix epoch and `time`, negative before 1970.
+#[cfg(all(target_os = "macos", any(feature = "chrono", feature = "time")))]
+fn unix_nanos(time: SystemTime) -> i128 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_nanos() as i128,
+        Err(error) => -(error.duration().as_nanos() as i128),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_date_is_2001() {
+        assert_eq!(
+            system_time_from_reference_interval(0.0),
+            Some(reference_date())
+        );
+        assert_eq!(reference_interval_from_system_time(reference_date()), 0.0);
+        assert_eq!(
+            reference_date().duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_secs(978_307_200)
+        );
+    }
+
+    #[test]
+    fn unix_epoch_is_a_negative_interval() {
+        assert_eq!(
+            system_time_from_reference_interval(-978_307_200.0),
+            Some(UNIX_EPOCH)
+        );
+        assert_eq!(
+            reference_interval_from_system_time(UNIX_EPOCH),
+            -978_307_200.0
+        );
+    }
+
+    #[test]
+    fn keeps_sub_second_precision() {
+        assert_eq!(
+            system_time_from_reference_interval(1.25),
+            Some(reference_date() + Duration::from_millis(1250))
+        );
+        assert_eq!(
+            reference_interval_from_system_time(reference_date() + Duration::from_millis(1250)),
+            1.25
+        );
+    }
+
+    #[test]
+    fn converts_negative_fractional_intervals_before_1970() {
+        // 1969-12-31T23:59:59.5Z
+        let time = UNIX_EPOCH - Duration::from_millis(500);
+
+        assert_eq!(
+            system_time_from_reference_interval(-978_307_200.5),
+            Some(time)
+        );
+        assert_eq!(reference_interval_from_system_time(time), -978_307_200.5);
+    }
+
+    #[test]
+    fn round_trips() {
+        for seconds in [0.0, 0.5, -0.5, 1.0e9, -1.0e9, 718_000_000.125, -3.0e10] {
+            let time = system_time_from_reference_interval(seconds).unwrap();
+            assert_eq!(reference_interval_from_system_time(time), seconds);
+        }
+
+        for time in [
+            UNIX_EPOCH,
+            UNIX_EPOCH + Duration::from_millis(1_700_000_000_250),
+            UNIX_EPOCH - Duration::from_millis(86_400_500),
+        ] {
+            let seconds = reference_interval_from_system_time(time);
+            assert_eq!(system_time_from_reference_interval(seconds), Some(time));
+        }
+    }
+
+    #[test]
+    fn rejects_unrepresentable_intervals() {
+        assert_eq!(system_time_from_reference_interval(f64::NAN), None);
+        assert_eq!(system_time_from_reference_interval(f64::INFINITY), None);
+        assert_eq!(system_time_from_reference_interval(f64::NEG_INFINITY), None);
+        assert_eq!(system_time_from_reference_interval(f64::MAX), None);
+    }
+}
@@ -0,0 +1,167 @@
+use cocoa::base::{nil, BOOL, YES};
+use cocoa::foundation::NSAutoreleasePool;
+
+use crate::{plist, prelude::*, ObserverToken};
+
+/// How a [`DistributedNotificationCenter`] delivers a notification to an observer while the
+/// center is suspended.
+#[repr(usize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SuspensionBehavior {
+    /// The notification is discarded.
+    Drop = 1,
+    /// Only the last notification with the same name and sender is delivered on resume.
+    Coalesce = 2,
+    /// Every notification is queued and delivered on resume.
+    Hold = 3,
+    /// The notification is delivered right away, as if the center weren't suspended.
+    DeliverImmediately = 4,
+}
+
+/// Rust wrapper around an `NSDistributedNotificationCenter`, which broadcasts notifications to
+/// every process of the user's session.
+///
+/// Notifications are identified by their name and an optional sender name, and can only carry a
+/// `userInfo` made of property list values.
+#[derive(CocoaType)]
+pub struct DistributedNotificationCenter {
+    ptr: Id,
+}
+
+#[allow(clippy::missing_safety_doc)]
+impl DistributedNotificationCenter {
+    #[cocoa_type_property(NSDistributedNotificationCenter, defaultCenter)]
+    pub unsafe fn default_center() -> DistributedNotificationCenter {}
+
+    /// Returns `true` if delivery to this process is suspended.
+    pub fn suspended(&self) -> bool {
+        let suspended: BOOL = unsafe { msg_send![self.ptr, suspended] };
+        suspended == YES
+    }
+
+    /// Suspends or resumes delivery to this process. While suspended, each observer's
+    /// [`SuspensionBehavior`] decides what happens to its notifications.
+    pub fn set_suspended(&self, suspended: bool) {
+        let suspended: BOOL = suspended.into();
+        unsafe {
+            let _: () = msg_send![self.ptr, setSuspended: suspended];
+        }
+    }
+
+    /// Calls `callback` with every notification named `name` that is posted under the sender
+    /// name `sender`.
+    ///
+    /// A `name` or `sender` of `None` matches any name or any sender. Callbacks run on the
+    /// thread whose run loop the center delivers on, normally the main thread.
+    pub fn observe<F>(
+        &self,
+        name: Option<&str>,
+        sender: Option<&str>,
+        suspension_behavior: SuspensionBehavior,
+        callback: F,
+    ) -> ObserverToken
+    where
+        F: FnMut(Notification) + Send + 'static,
+    {
+        unsafe {
+            let name = name.map(|name| NS_String::from(name).ptr()).unwrap_or(nil);
+            let sender = sender
+                .map(|sender| NS_String::from(sender).ptr())
+                .unwrap_or(nil);
+            let suspension_behavior = suspension_behavior as NS_uint;
+
+            ObserverToken::register(self.ptr, callback, |observer, selector| {
+                let _: () = msg_send![self.ptr, addObserver:observer
+                                                   selector:selector
+                                                       name:name
+                                                     object:sender
+                                         suspensionBehavior:suspension_behavior];
+            })
+        }
+    }
+
+    /// Broadcasts a notification named `name` under the sender name `sender`.
+    ///
+    /// With `deliver_immediately`, the notification is delivered even to observers whose center
+    /// is suspended, regardless of their [`SuspensionBehavior`].
+    pub fn post(
+        &self,
+        name: &str,
+        sender: Option<&str>,
+        user_info: Option<&plist::Dictionary>,
+        deliver_immediately: bool,
+    ) {
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+            let name = NS_String::from(name);
+            let sender = sender
+                .map(|sender| NS_String::from(sender).ptr())
+                .unwrap_or(nil);
+            let user_info = user_info
+                .map(|user_info| Dict::from_plist(user_info).ptr())
+                .unwrap_or(nil);
+            let deliver_immediately: BOOL = deliver_immediately.into();
+
+            let _: () = msg_send![self.ptr, postNotificationName:name.ptr()
+                                                          object:sender
+                                                        userInfo:user_info
+                                              deliverImmediately:deliver_immediately];
+            pool.drain();
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl DistributedNotificationCenter {
+    /// Broadcasts `payload` as the `userInfo` of a notification.
+    ///
+    /// The payload must serialize to a map, such as a struct, of property list values; see
+    /// [`plist::to_value`].
+    pub fn post_payload<T: serde::Serialize + ?Sized>(
+        &self,
+        name: &str,
+        sender: Option<&str>,
+        payload: &T,
+        deliver_immediately: bool,
+    ) -> Result<(), plist::Error> {
+        match plist::to_value(payload)? {
+            plist::Value::Dictionary(user_info) => {
+                self.post(name, sender, Some(&user_info), deliver_immediately);
+                Ok(())
+            }
+            _ => Err(plist::Error::Serde(
+                "notification payloads must serialize to a map".to_string(),
+            )),
+        }
+    }
+
+    /// Calls `callback` with the `userInfo` of every matching notification, deserialized as `T`.
+    ///
+    /// A missing `userInfo` is treated as empty. Notifications whose `userInfo` doesn't match `T`
+    /// are passed on as errors.
+    pub fn observe_payload<T, F>(
+        &self,
+        name: &str,
+        sender: Option<&str>,
+        suspension_behavior: SuspensionBehavior,
+        mut callback: F,
+    ) -> ObserverToken
+    where
+        T: serde::de::DeserializeOwned,
+        F: FnMut(Result<T, plist::Error>) + Send + 'static,
+    {
+        self.observe(
+            Some(name),
+            sender,
+            suspension_behavior,
+            move |notification| {
+                let user_info = notification
+                    .user_info()
+                    .map(|user_info| user_info.to_plist())
+                    .unwrap_or_default();
+
+                callback(plist::from_value(plist::Value::Dictionary(user_info)));
+            },
+        )
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use crate::plist;
+
+#[cfg(target_os = "macos")]
+use crate::prelude::*;
+#[cfg(target_os = "macos")]
+use cocoa::base::{nil, BOOL, NO, YES};
+
+/// An error raised by a Cocoa API.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CocoaError {
+    /// A method returned nil (or `NO`) where a value was required, without reporting why.
+    NilReturn { selector: String },
+    /// An object wasn't an instance of the expected class.
+    ClassMismatch { expected: String, found: String },
+    /// An Objective-C exception was raised.
+    Exception {
+        name: Option<String>,
+        reason: Option<String>,
+    },
+    /// A method reported failure through an `NSError`.
+    NSError(NSErrorInfo),
+    /// An argument was rejected before any Objective-C method was called.
+    InvalidArgument { message: String },
+}
+
+/// The contents of an `NSError`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NSErrorInfo {
+    pub domain: String,
+    pub code: isize,
+    pub localized_description: String,
+    /// The entries of the error's `userInfo` that are property list values.
+    pub user_info: plist::Dictionary,
+}
+
+#[cfg(target_os = "macos")]
+impl CocoaError {
+    pub(crate) fn nil_return(selector: impl Into<String>) -> CocoaError {
+        CocoaError::NilReturn {
+            selector: selector.into(),
+        }
+    }
+
+    pub(crate) fn invalid_argument(message: impl Into<String>) -> CocoaError {
+        CocoaError::InvalidArgument {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CocoaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CocoaError::NilReturn { selector } => write!(f, "`{selector}` returned nil"),
+            CocoaError::ClassMismatch { expected, found } => {
+                write!(f, "expected an instance of {expected}, found {found}")
+            }
+            CocoaError::Exception { name, reason } => write!(
+                f,
+                "Objective-C exception {}: {}",
+                name.as_deref().unwrap_or("(unnamed)"),
+                reason.as_deref().unwrap_or("(no reason)")
+            ),
+            CocoaError::NSError(error) => write!(
+                f,
+                "{} (domain {}, code {})",
+                error.localized_description, error.domain, error.code
+            ),
+            CocoaError::InvalidArgument { message } => write!(f, "invalid argument: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CocoaError {}
+
+#[cfg(target_os = "macos")]
+impl NSErrorInfo {
+    /// Reads the contents of an `NSError`.
+    ///
+    /// # Safety
+    ///
+    /// `error` must point to a valid `NSError`.
+    pub unsafe fn from_ptr(error: Id) -> NSErrorInfo {
+        let string = |ptr: Id| {
+            NS_String::from_ptr(ptr)
+                .map(|string| string.to_string())
+                .unwrap_or_default()
+        };
+
+        let code: NS_int = msg_send![error, code];
+        let domain: Id = msg_send![error, domain];
+        let localized_description: Id = msg_send![error, localizedDescription];
+        let user_info: Id = msg_send![error, userInfo];
+
+        NSErrorInfo {
+            domain: string(domain),
+            code: code as isize,
+            localized_description: string(localized_description),
+            user_info: Dict::from_ptr(user_info)
+                .map(|user_info| user_info.to_plist())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A return value that signals failure of a method with an `NSError **` out-parameter.
+#[cfg(target_os = "macos")]
+pub trait NSErrorReturn {
+    /// Returns `true` if the value indicates that the method failed.
+    fn is_failure(&self) -> bool;
+}
+
+#[cfg(target_os = "macos")]
+impl NSErrorReturn for BOOL {
+    fn is_failure(&self) -> bool {
+        *self == NO
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl NSErrorReturn for Id {
+    fn is_failure(&self) -> bool {
+        self.is_null()
+    }
+}
+
+/// Calls a method that takes an `NSError **` out-parameter and converts its result.
+///
+/// `call` receives the out-parameter to pass along. If the method returns `NO` or nil, the error
+/// it wrote is returned as [`CocoaError::NSError`], or [`CocoaError::NilReturn`] if it didn't
+/// write one.
+///
+/// # Safety
+///
+/// `call` must only pass the pointer to methods that write an autoreleased `NSError` or nothing.
+#[cfg(target_os = "macos")]
+pub unsafe fn ns_error_result<R: NSErrorReturn>(
+    selector: &str,
+    call: impl FnOnce(*mut Id) -> R,
+) -> Result<R, CocoaError> {
+    let mut error: Id = nil;
+    let result = call(&mut error);
+
+    if !result.is_failure() {
+        Ok(result)
+    } else if error.is_null() {
+        Err(CocoaError::nil_return(selector))
+    } else {
+        Err(CocoaError::NSError(NSErrorInfo::from_ptr(error)))
+    }
+}
+
+/// Runs `f`, converting any Objective-C exception it raises into [`CocoaError::Exception`].
+///
+/// Rust code inside `f` must not panic, since unwinding through the exception handler is
+/// undefined behaviour.
+#[cfg(target_os = "macos")]
+pub fn catch_exception<R>(f: impl FnOnce() -> R) -> Result<R, CocoaError> {
+    unsafe {
+        objc_exception::r#try(f).map_err(|exception| {
+            let exception = exception as Id;
+
+            if exception.is_null() {
+                return CocoaError::Exception {
+                    name: None,
+                    reason: None,
+                };
+            }
+
+            let name: Id = msg_send![exception, name];
+            let reason: Id = msg_send![exception, reason];
+
+            CocoaError::Exception {
+                name: NS_String::from_ptr(name).map(|name| name.to_string()),
+                reason: NS_String::from_ptr(reason).map(|reason| reason.to_string()),
+            }
+        })
+    }
+}
+
+/// Checks that `ptr` is a non-nil instance of the Objective-C class `expected` and wraps it.
+///
+/// # Safety
+///
+/// `ptr` must be nil or point to a valid Objective-C object.
+#[cfg(target_os = "macos")]
+pub unsafe fn downcast<T: CocoaType>(ptr: Id, expected: &str) -> Result<T, CocoaError> {
+    let object = ptr.as_ref().ok_or_else(|| CocoaError::ClassMismatch {
+        expected: expected.to_string(),
+        found: "nil".to_string(),
+    })?;
+
+    let is_kind: BOOL = match objc::runtime::Class::get(expected) {
+        Some(class) => msg_send![ptr, isKindOfClass: class],
+        None => NO,
+    };
+
+    if is_kind != YES {
+        return Err(CocoaError::ClassMismatch {
+            expected: expected.to_string(),
+            found: object.class().name().to_string(),
+        });
+    }
+
+    T::from_ptr(ptr).ok_or_else(|| CocoaError::nil_return(expected))
+}
@@ -1,44 +1,93 @@
-mod app_watcher;
+pub mod app_watcher;
+#[cfg(target_os = "macos")]
+mod array;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(target_os = "macos")]
 mod cocoa_type;
+#[cfg(target_os = "macos")]
+mod data;
+mod date;
+#[cfg(target_os = "macos")]
 mod dict;
+#[cfg(target_os = "macos")]
+mod distributed_notification_center;
+mod error;
+#[cfg(target_os = "macos")]
+mod notification;
+#[cfg(target_os = "macos")]
 mod notification_center;
 mod notification_type;
+#[cfg(target_os = "macos")]
+mod number;
+#[cfg(target_os = "macos")]
 mod object;
+#[cfg(target_os = "macos")]
+mod observer;
+#[cfg(target_os = "macos")]
 mod running_application;
+#[cfg(target_os = "macos")]
 mod string;
+#[cfg(target_os = "macos")]
 mod url;
+#[cfg(target_os = "macos")]
 mod workspace;
 
+pub mod plist;
+
+#[cfg(target_os = "macos")]
 pub mod prelude;
 
 pub use app_watcher::*;
+#[cfg(target_os = "macos")]
+pub use array::*;
+#[cfg(target_os = "macos")]
+pub use data::*;
+pub use date::*;
+#[cfg(target_os = "macos")]
 pub use dict::*;
+#[cfg(target_os = "macos")]
+pub use distributed_notification_center::*;
+pub use error::*;
+#[cfg(target_os = "macos")]
+pub use notification::*;
+#[cfg(target_os = "macos")]
 pub use notification_center::*;
 pub use notification_type::*;
+#[cfg(target_os = "macos")]
+pub use number::*;
+#[cfg(target_os = "macos")]
+pub use observer::*;
+#[cfg(target_os = "macos")]
 pub use running_application::*;
+#[cfg(target_os = "macos")]
 pub use string::*;
+#[cfg(target_os = "macos")]
 pub use url::*;
+#[cfg(target_os = "macos")]
 pub use workspace::*;
 
+#[cfg(target_os = "macos")]
 #[link(name = "Foundation", kind = "framework")]
 #[link(name = "AppKit", kind = "framework")]
 extern "C" {}
 
+#[cfg(target_os = "macos")]
 #[macro_use]
 extern crate objc;
 
 #[allow(non_camel_case_types)]
-#[cfg(target_pointer_width = "32")]
+#[cfg(all(target_os = "macos", target_pointer_width = "32"))]
 pub type NS_uint = libc::c_uint;
 
 #[allow(non_camel_case_types)]
-#[cfg(target_pointer_width = "64")]
+#[cfg(all(target_os = "macos", target_pointer_width = "64"))]
 pub type NS_uint = libc::c_ulong;
 
 #[allow(non_camel_case_types)]
-#[cfg(target_pointer_width = "32")]
+#[cfg(all(target_os = "macos", target_pointer_width = "32"))]
 pub type NS_int = libc::c_int;
 
 #[allow(non_camel_case_types)]
-#[cfg(target_pointer_width = "64")]
+#[cfg(all(target_os = "macos", target_pointer_width = "64"))]
 pub type NS_int = libc::c_long;
@@ -0,0 +1,57 @@
+use cocoa::base::nil;
+
+use crate::prelude::*;
+
+/// Rust wrapper around an `NSNotification`.
+#[derive(CocoaType)]
+pub struct Notification {
+    ptr: Id,
+}
+
+impl Notification {
+    /// Allocates a new `Notification` with the given name, sender and `userInfo`.
+    pub fn new(name: &str, object: Option<Id>, user_info: Option<&Dict>) -> Notification {
+        let ptr: Id = unsafe {
+            let name = NS_String::from(name);
+            let object = object.unwrap_or(nil);
+            let user_info = user_info.map(|user_info| user_info.ptr()).unwrap_or(nil);
+
+            let notification: Id = msg_send![class!(NSNotification), alloc];
+            msg_send![notification, initWithName:name.ptr() object:object userInfo:user_info]
+        };
+
+        Notification { ptr }
+    }
+
+    /// Returns the name of the notification.
+    pub fn name(&self) -> String {
+        unsafe {
+            let name: Id = msg_send![self.ptr, name];
+
+            NS_String::from_ptr(name)
+                .map(|name| name.to_string())
+                .unwrap_or_default()
+        }
+    }
+
+    /// Returns the object that posted the notification, if any.
+    pub fn object(&self) -> Option<Id> {
+        unsafe {
+            let object: Id = msg_send![self.ptr, object];
+
+            if object.is_null() {
+                None
+            } else {
+                Some(object)
+            }
+        }
+    }
+
+    /// Returns the notification's `userInfo` dictionary, if it has one.
+    pub fn user_info(&self) -> Option<Dict> {
+        unsafe {
+            let user_info: Id = msg_send![self.ptr, userInfo];
+            Dict::from_ptr(user_info)
+        }
+    }
+}
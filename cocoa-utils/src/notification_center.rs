@@ -1,17 +1,92 @@
 use cocoa::{base::nil, foundation::NSAutoreleasePool};
 
-use crate::{prelude::*, NSAppWatcher, NotificationType};
+use crate::{plist, prelude::*, NSAppWatcher, Notification, NotificationType, ObserverToken};
 
 #[derive(CocoaType)]
 pub struct NotificationCenter {
     ptr: Id,
 }
 
-#[allow(clippy::missing_safety_doc)]
+#[allow(clippy::missing_safety_doc, clippy::new_without_default)]
 impl NotificationCenter {
     #[cocoa_type_property(NSNotificationCenter, defaultCenter)]
     pub unsafe fn default_center() -> Option<NotificationCenter> {}
 
+    /// Allocates a new, private notification center.
+    ///
+    /// Only observers added to this center receive the notifications posted to it, which keeps
+    /// a component's internal notifications (or a test's synthetic ones) away from the rest of
+    /// the process.
+    pub fn new() -> NotificationCenter {
+        let ptr: Id = unsafe {
+            let center: Id = msg_send![class!(NSNotificationCenter), alloc];
+            msg_send![center, init]
+        };
+
+        NotificationCenter { ptr }
+    }
+
+    /// Posts `notification`, synchronously calling every matching observer on this thread.
+    pub fn post(&self, notification: &Notification) {
+        unsafe {
+            let _: () = msg_send![self.ptr(), postNotification: notification.ptr()];
+        }
+    }
+
+    /// Creates and posts a notification with the given name, sender and `userInfo`.
+    pub fn post_name(&self, name: &str, object: Option<Id>, user_info: Option<&Dict>) {
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+            let name = NS_String::from(name);
+            let object = object.unwrap_or(nil);
+            let user_info = user_info.map(|user_info| user_info.ptr()).unwrap_or(nil);
+
+            let _: () = msg_send![self.ptr(), postNotificationName:name.ptr()
+                                                           object:object
+                                                         userInfo:user_info];
+            pool.drain();
+        }
+    }
+
+    /// Creates and posts a notification whose `userInfo` is converted from a property list
+    /// dictionary.
+    pub fn post_name_with_plist(
+        &self,
+        name: &str,
+        object: Option<Id>,
+        user_info: &plist::Dictionary,
+    ) {
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+            self.post_name(name, object, Some(&Dict::from_plist(user_info)));
+            pool.drain();
+        }
+    }
+
+    /// Calls `callback` with every notification named `name` that `object` posts.
+    ///
+    /// A `name` or `object` of `None` matches any name or any sender. The callback runs
+    /// synchronously on the thread that posts the notification, and stays registered until the
+    /// returned token is dropped. If the callback panics, the panic is caught and the
+    /// notification is dropped.
+    pub fn observe<F>(&self, name: Option<&str>, object: Option<Id>, callback: F) -> ObserverToken
+    where
+        F: FnMut(Notification) + Send + 'static,
+    {
+        unsafe {
+            let name = name.map(NS_String::from);
+            let name = name.map(|name| name.ptr()).unwrap_or(nil);
+            let object = object.unwrap_or(nil);
+
+            ObserverToken::register(self.ptr(), callback, |observer, selector| {
+                let _: () = msg_send![self.ptr(), addObserver:observer
+                                                     selector:selector
+                                                         name:name
+                                                       object:object];
+            })
+        }
+    }
+
     pub unsafe fn add_observer(
         &mut self,
         observer: &NSAppWatcher,
@@ -34,7 +109,7 @@ impl NotificationCenter {
         unsafe {
             let pool = NSAutoreleasePool::new(nil);
             let name = NS_String::from(notification_type.get_name());
-            let _: () = msg_send![self.ptr(), removeObserver:observer name:name object:nil];
+            let _: () = msg_send![self.ptr(), removeObserver:observer.ptr() name:name object:nil];
             pool.drain();
         }
     }
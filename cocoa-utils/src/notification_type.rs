@@ -1,5 +1,7 @@
 #[repr(C)]
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum NotificationType {
     Launching = 0,
     Launched,
@@ -8,9 +10,39 @@ pub enum NotificationType {
     Unhidden,
     Activated,
     Deactivated,
+    /// A synthetic notification for an application that was already running when a watcher
+    /// started, see `AppWatcherBuilder::snapshot`. It's never posted by the workspace.
+    #[cfg_attr(feature = "serde", serde(rename = "already_running"))]
+    AlreadyRunning,
 }
 
 impl NotificationType {
+    /// Every notification posted by the workspace, in the order the variants are declared.
+    pub const ALL: [NotificationType; 7] = [
+        NotificationType::Launching,
+        NotificationType::Launched,
+        NotificationType::Terminated,
+        NotificationType::Hidden,
+        NotificationType::Unhidden,
+        NotificationType::Activated,
+        NotificationType::Deactivated,
+    ];
+
+    /// The notification's lowercase name, as it's serialized, e.g. `already_running`.
+    pub fn as_str(&self) -> &'static str {
+        use NotificationType::*;
+        match self {
+            Launching => "launching",
+            Launched => "launched",
+            Terminated => "terminated",
+            Hidden => "hidden",
+            Unhidden => "unhidden",
+            Activated => "activated",
+            Deactivated => "deactivated",
+            AlreadyRunning => "already_running",
+        }
+    }
+
     pub fn get_name(&self) -> &'static str {
         use NotificationType::*;
         match self {
@@ -21,9 +53,11 @@ impl NotificationType {
             Unhidden => "NSWorkspaceDidUnhideApplicationNotification",
             Activated => "NSWorkspaceDidActivateApplicationNotification",
             Deactivated => "NSWorkspaceDidDeactivateApplicationNotification",
+            AlreadyRunning => "CocoaUtilsApplicationAlreadyRunningNotification",
         }
     }
 
+    #[cfg(target_os = "macos")]
     pub fn get_sel(&self) -> objc::runtime::Sel {
         use NotificationType::*;
         match self {
@@ -34,10 +68,12 @@ impl NotificationType {
             Unhidden => sel!(applicationUnhidden:),
             Activated => sel!(applicationActivated:),
             Deactivated => sel!(applicationDeactivated:),
+            AlreadyRunning => sel!(applicationAlreadyRunning:),
         }
     }
 }
 
+#[cfg(target_os = "macos")]
 unsafe impl objc::Encode for NotificationType {
     fn encode() -> objc::Encoding {
         unsafe { objc::Encoding::from_str("i") }
@@ -54,6 +90,7 @@ impl std::fmt::Debug for NotificationType {
             Self::Unhidden => write!(f, "unhidden"),
             Self::Activated => write!(f, "activated"),
             Self::Deactivated => write!(f, "deactivated"),
+            Self::AlreadyRunning => write!(f, "already running"),
         }
     }
 }
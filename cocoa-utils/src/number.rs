@@ -0,0 +1,125 @@
+use cocoa::base::{BOOL, YES};
+use core_foundation::base::{CFGetTypeID, TCFType};
+use core_foundation::boolean::CFBoolean;
+
+use crate::plist;
+use crate::prelude::*;
+
+/// Rust wrapper around an `NSNumber`.
+#[derive(CocoaType)]
+pub struct Number {
+    ptr: Id,
+}
+
+impl Number {
+    /// Allocates a new `Number` holding a boolean.
+    pub fn from_bool(value: bool) -> Number {
+        let value: BOOL = value.into();
+        let ptr: Id = unsafe {
+            let number: Id = msg_send![class!(NSNumber), alloc];
+            msg_send![number, initWithBool: value]
+        };
+
+        Number { ptr }
+    }
+
+    /// Allocates a new `Number` holding an integer.
+    pub fn from_i64(value: i64) -> Number {
+        let ptr: Id = unsafe {
+            let number: Id = msg_send![class!(NSNumber), alloc];
+            msg_send![number, initWithLongLong: value]
+        };
+
+        Number { ptr }
+    }
+
+    /// Allocates a new `Number` holding a float.
+    pub fn from_f64(value: f64) -> Number {
+        let ptr: Id = unsafe {
+            let number: Id = msg_send![class!(NSNumber), alloc];
+            msg_send![number, initWithDouble: value]
+        };
+
+        Number { ptr }
+    }
+
+    /// Returns `true` if the number is one of the `kCFBooleanTrue`/`kCFBooleanFalse` singletons.
+    pub fn is_bool(&self) -> bool {
+        unsafe { CFGetTypeID(self.ptr as _) == CFBoolean::type_id() }
+    }
+
+    /// Returns `true` if the number holds a `float` or `double`.
+    pub fn is_float(&self) -> bool {
+        unsafe {
+            let objc_type: *const libc::c_char = msg_send![self.ptr, objCType];
+            matches!(*objc_type as u8, b'f' | b'd')
+        }
+    }
+
+    pub fn as_bool(&self) -> bool {
+        let value: BOOL = unsafe { msg_send![self.ptr, boolValue] };
+        value == YES
+    }
+
+    pub fn as_i64(&self) -> i64 {
+        unsafe { msg_send![self.ptr, longLongValue] }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        unsafe { msg_send![self.ptr, doubleValue] }
+    }
+
+    /// Converts the number to a [`plist::Value::Boolean`], [`plist::Value::Real`] or
+    /// [`plist::Value::Integer`], depending on what it holds.
+    pub fn to_plist(&self) -> plist::Value {
+        if self.is_bool() {
+            plist::Value::Boolean(self.as_bool())
+        } else if self.is_float() {
+            plist::Value::Real(self.as_f64())
+        } else {
+            plist::Value::Integer(self.as_i64())
+        }
+    }
+}
+
+impl From<bool> for Number {
+    fn from(value: bool) -> Self {
+        Number::from_bool(value)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Number::from_i64(value)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::from_f64(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Number {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_plist().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Number {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected};
+
+        match plist::Value::deserialize(deserializer)? {
+            plist::Value::Boolean(value) => Ok(Number::from_bool(value)),
+            plist::Value::Integer(value) => Ok(Number::from_i64(value)),
+            plist::Value::Real(value) => Ok(Number::from_f64(value)),
+            _ => Err(D::Error::invalid_type(
+                Unexpected::Other("non-numeric value"),
+                &"a boolean or a number",
+            )),
+        }
+    }
+}
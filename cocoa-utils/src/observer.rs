@@ -0,0 +1,156 @@
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, Once, PoisonError};
+
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Sel};
+
+use crate::prelude::*;
+use crate::Notification;
+
+const OBSERVER_CLASS_NAME: &str = "CocoaUtilsNotificationObserver";
+const CALLBACK_IVAR: &str = "rustCallback";
+
+type Callback = Mutex<Box<dyn FnMut(Notification) + Send>>;
+
+static DECLARE_OBSERVER_CLASS: Once = Once::new();
+
+thread_local! {
+    /// The callbacks currently running on this thread, used to detect re-entrant notifications.
+    static RUNNING_CALLBACKS: RefCell<Vec<*const Callback>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns the Objective-C class whose instances forward notifications to a Rust closure,
+/// declaring it on first use.
+fn observer_class() -> &'static Class {
+    DECLARE_OBSERVER_CLASS.call_once(|| {
+        let mut cls = ClassDecl::new(OBSERVER_CLASS_NAME, class!(NSObject)).unwrap();
+
+        unsafe {
+            cls.add_method(
+                sel!(handleNotification:),
+                handle_notification as extern "C" fn(&Object, Sel, Id),
+            );
+            cls.add_method(sel!(dealloc), dealloc as extern "C" fn(&Object, Sel));
+
+            cls.add_ivar::<*mut c_void>(CALLBACK_IVAR);
+        }
+
+        cls.register();
+    });
+
+    Class::get(OBSERVER_CLASS_NAME).unwrap()
+}
+
+extern "C" fn handle_notification(observer: &Object, _cmd: Sel, notification: Id) {
+    unsafe {
+        let callback = *observer.get_ivar::<*mut c_void>(CALLBACK_IVAR) as *const Callback;
+
+        if callback.is_null() {
+            return;
+        }
+
+        // The observer owns one reference until it is deallocated, which can't happen while one
+        // of its methods runs. Taking another keeps the closure alive for the whole call, even if
+        // the token is dropped on another thread meanwhile.
+        Arc::increment_strong_count(callback);
+        let callback = Arc::from_raw(callback);
+
+        let Some(notification) = Notification::from_ptr(notification) else {
+            return;
+        };
+
+        // A notification posted from inside the callback would wait forever on the lock this
+        // thread already holds, so it is dropped instead.
+        let reentrant = RUNNING_CALLBACKS.with(|running| {
+            let mut running = running.borrow_mut();
+            let reentrant = running.contains(&Arc::as_ptr(&callback));
+
+            if !reentrant {
+                running.push(Arc::as_ptr(&callback));
+            }
+
+            reentrant
+        });
+
+        if reentrant {
+            return;
+        }
+
+        // Unwinding into Objective-C is undefined behaviour, so a panicking callback only
+        // loses the notification it was handling.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut callback = callback.lock().unwrap_or_else(PoisonError::into_inner);
+            callback(notification);
+        }));
+
+        RUNNING_CALLBACKS.with(|running| running.borrow_mut().pop());
+    }
+}
+
+extern "C" fn dealloc(observer: &Object, _cmd: Sel) {
+    unsafe {
+        let callback = *observer.get_ivar::<*mut c_void>(CALLBACK_IVAR) as *const Callback;
+
+        if !callback.is_null() {
+            drop(Arc::from_raw(callback));
+        }
+
+        let _: () = msg_send![super(observer, class!(NSObject)), dealloc];
+    }
+}
+
+/// Keeps a closure-based notification observer registered.
+///
+/// The observer is removed from its notification center when the token is dropped, and the
+/// closure is dropped once no thread is still running it.
+///
+/// Notifications are delivered to the closure one at a time. A notification that the closure
+/// itself causes to be posted to the same observer, on the same thread, is not delivered, since
+/// the closure is still busy with the one that triggered it.
+#[must_use = "the observer is removed as soon as the token is dropped"]
+pub struct ObserverToken {
+    center: Id,
+    observer: Id,
+}
+
+// The closure is `Send`, and notification centers can be used from any thread.
+unsafe impl Send for ObserverToken {}
+
+impl ObserverToken {
+    /// Creates an observer object that forwards to `callback`, and passes it to `add` along with
+    /// the selector to register on `center`.
+    ///
+    /// # Safety
+    ///
+    /// `center` must point to a valid `NSNotificationCenter` (or subclass), and `add` must
+    /// register the observer with that center and nothing else.
+    pub(crate) unsafe fn register<F>(center: Id, callback: F, add: impl FnOnce(Id, Sel)) -> Self
+    where
+        F: FnMut(Notification) + Send + 'static,
+    {
+        let callback: Box<dyn FnMut(Notification) + Send> = Box::new(callback);
+        let callback: *const Callback = Arc::into_raw(Arc::new(Mutex::new(callback)));
+
+        let observer: Id = msg_send![observer_class(), new];
+        (*observer).set_ivar::<*mut c_void>(CALLBACK_IVAR, callback as *mut c_void);
+
+        let center: Id = msg_send![center, retain];
+        add(observer, sel!(handleNotification:));
+
+        ObserverToken { center, observer }
+    }
+}
+
+impl Drop for ObserverToken {
+    fn drop(&mut self) {
+        unsafe {
+            // The observer releases its reference to the closure when it is deallocated, and a
+            // call still running on another thread holds a reference of its own.
+            let _: () = msg_send![self.center, removeObserver: self.observer];
+            let _: () = msg_send![self.observer, release];
+            let _: () = msg_send![self.center, release];
+        }
+    }
+}
@@ -0,0 +1,591 @@
+//! The `bplist00` binary property list format.
+//!
+//! A binary property list is a header, a flat table of objects, a table of object offsets and a
+//! 32-byte trailer. Containers refer to their children by index into the offset table.
+
+use std::cell::Cell;
+use std::io::Write;
+
+use super::{Dictionary, Error, Value};
+use crate::date::{reference_interval_from_system_time, system_time_from_reference_interval};
+
+pub(crate) const MAGIC: &[u8] = b"bplist00";
+
+const TRAILER_LEN: usize = 32;
+
+/// Nesting deeper than this is rejected instead of risking a stack overflow.
+const MAX_DEPTH: usize = 512;
+
+/// Objects referenced from several containers are decoded once per reference, so reading more
+/// objects than this many times the object count is rejected instead of letting a small file
+/// expand exponentially.
+const MAX_EXPANSION: usize = 16;
+
+pub(crate) fn read(bytes: &[u8]) -> Result<Value, Error> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(Error::binary(0, "missing bplist00 header"));
+    }
+
+    if bytes.len() < MAGIC.len() + TRAILER_LEN {
+        return Err(Error::binary(0, "file is too short"));
+    }
+
+    let trailer_start = bytes.len() - TRAILER_LEN;
+    let trailer = &bytes[trailer_start..];
+
+    let offset_size = trailer[6] as usize;
+    let ref_size = trailer[7] as usize;
+    let object_count = read_uint(&trailer[8..16]);
+    let top_object = read_uint(&trailer[16..24]);
+    let offset_table_start = read_uint(&trailer[24..32]);
+
+    if !(1..=8).contains(&offset_size) || !(1..=8).contains(&ref_size) {
+        return Err(Error::binary(
+            trailer_start,
+            "invalid integer sizes in trailer",
+        ));
+    }
+
+    let offset_table_len = object_count
+        .checked_mul(offset_size as u64)
+        .ok_or_else(|| Error::binary(trailer_start, "object count is too large"))?;
+
+    if offset_table_start < MAGIC.len() as u64
+        || offset_table_start
+            .checked_add(offset_table_len)
+            .is_none_or(|end| end > trailer_start as u64)
+    {
+        return Err(Error::binary(
+            trailer_start,
+            "offset table is out of bounds",
+        ));
+    }
+
+    if top_object >= object_count {
+        return Err(Error::binary(trailer_start, "top object is out of bounds"));
+    }
+
+    let offset_table_start = offset_table_start as usize;
+    let object_count = object_count as usize;
+
+    let offsets = (0..object_count)
+        .map(|index| {
+            let start = offset_table_start + index * offset_size;
+            let offset = read_uint(&bytes[start..start + offset_size]) as usize;
+
+            if offset < MAGIC.len() || offset >= offset_table_start {
+                Err(Error::binary(
+                    start,
+                    format!("offset of object {index} is out of bounds"),
+                ))
+            } else {
+                Ok(offset)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let reader = Reader {
+        bytes: &bytes[..offset_table_start],
+        offsets,
+        ref_size,
+        remaining: Cell::new(object_count.saturating_mul(MAX_EXPANSION)),
+    };
+
+    reader.read_object(top_object as usize, &mut Vec::new())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offsets: Vec<usize>,
+    ref_size: usize,
+    /// How many more objects may be decoded, see [`MAX_EXPANSION`].
+    remaining: Cell<usize>,
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&self, start: usize, len: usize) -> Result<&'a [u8], Error> {
+        start
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .map(|end| &self.bytes[start..end])
+            .ok_or_else(|| Error::binary(start, "object extends past the object table"))
+    }
+
+    /// Reads object `index`. `stack` holds the containers being read, to reject cycles.
+    fn read_object(&self, index: usize, stack: &mut Vec<usize>) -> Result<Value, Error> {
+        let offset = *self.offsets.get(index).ok_or_else(|| {
+            Error::binary(0, format!("object reference {index} is out of bounds"))
+        })?;
+
+        if stack.contains(&index) {
+            return Err(Error::binary(offset, "object graph contains a cycle"));
+        }
+
+        if stack.len() >= MAX_DEPTH {
+            return Err(Error::binary(offset, "objects are nested too deeply"));
+        }
+
+        let remaining = self.remaining.get().checked_sub(1).ok_or_else(|| {
+            Error::binary(
+                offset,
+                "shared object references expand to too many objects",
+            )
+        })?;
+        self.remaining.set(remaining);
+
+        let marker = self.slice(offset, 1)?[0];
+        let (kind, info) = (marker >> 4, marker & 0x0f);
+
+        let value = match (kind, info) {
+            (0x0, 0x8) => Value::Boolean(false),
+            (0x0, 0x9) => Value::Boolean(true),
+            (0x1, 0..=4) => {
+                let len = 1usize << info;
+                let bytes = self.slice(offset + 1, len)?;
+                Value::Integer(read_int(bytes, offset)?)
+            }
+            (0x2, 2) => {
+                let bytes = self.slice(offset + 1, 4)?;
+                Value::Real(f32::from_be_bytes(bytes.try_into().unwrap()).into())
+            }
+            (0x2, 3) => {
+                let bytes = self.slice(offset + 1, 8)?;
+                Value::Real(f64::from_be_bytes(bytes.try_into().unwrap()))
+            }
+            (0x3, 3) => {
+                let bytes = self.slice(offset + 1, 8)?;
+                let seconds = f64::from_be_bytes(bytes.try_into().unwrap());
+                let date = system_time_from_reference_interval(seconds)
+                    .ok_or_else(|| Error::binary(offset, "date is out of range"))?;
+                Value::Date(date)
+            }
+            (0x4, _) => {
+                let (len, start) = self.read_length(offset, info)?;
+                Value::Data(self.slice(start, len)?.to_vec())
+            }
+            (0x5, _) => {
+                let (len, start) = self.read_length(offset, info)?;
+                let bytes = self.slice(start, len)?;
+
+                if !bytes.is_ascii() {
+                    return Err(Error::binary(
+                        offset,
+                        "ASCII string contains non-ASCII bytes",
+                    ));
+                }
+
+                Value::String(String::from_utf8(bytes.to_vec()).unwrap())
+            }
+            (0x6, _) => {
+                let (len, start) = self.read_length(offset, info)?;
+                let len = len
+                    .checked_mul(2)
+                    .ok_or_else(|| Error::binary(offset, "string is too long"))?;
+                let units = self
+                    .slice(start, len)?
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                    .collect::<Vec<_>>();
+                let string = String::from_utf16(&units)
+                    .map_err(|_| Error::binary(offset, "UTF-16 string is malformed"))?;
+                Value::String(string)
+            }
+            (0x8, _) => {
+                let bytes = self.slice(offset + 1, info as usize + 1)?;
+                if bytes.len() > 8 {
+                    return Err(Error::binary(offset, "UID is wider than 64 bits"));
+                }
+                Value::Uid(read_uint(bytes))
+            }
+            (0xa, _) => {
+                let (len, start) = self.read_length(offset, info)?;
+                let refs = self.read_refs(start, len)?;
+
+                stack.push(index);
+                let array = refs
+                    .into_iter()
+                    .map(|child| self.read_object(child, stack))
+                    .collect::<Result<Vec<_>, _>>()?;
+                stack.pop();
+
+                Value::Array(array)
+            }
+            (0xd, _) => {
+                let (len, start) = self.read_length(offset, info)?;
+                let refs = self.read_refs(start, len.saturating_mul(2))?;
+                let (key_refs, value_refs) = refs.split_at(len);
+
+                stack.push(index);
+                let mut dictionary = Dictionary::new();
+                for (key_ref, value_ref) in key_refs.iter().zip(value_refs) {
+                    let key = match self.read_object(*key_ref, stack)? {
+                        Value::String(key) => key,
+                        _ => return Err(Error::binary(offset, "dictionary key is not a string")),
+                    };
+
+                    dictionary.insert(key, self.read_object(*value_ref, stack)?);
+                }
+                stack.pop();
+
+                Value::Dictionary(dictionary)
+            }
+            _ => {
+                return Err(Error::binary(
+                    offset,
+                    format!("unsupported object marker {marker:#04x}"),
+                ))
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Reads the length of a variable-length object, which is either the low nibble of the marker
+    /// or, when that's `0xf`, an integer object that follows it. Returns the length and the offset
+    /// of the object's contents.
+    fn read_length(&self, offset: usize, info: u8) -> Result<(usize, usize), Error> {
+        if info != 0x0f {
+            return Ok((info as usize, offset + 1));
+        }
+
+        let marker = self.slice(offset + 1, 1)?[0];
+        if marker >> 4 != 0x1 || marker & 0x0f > 3 {
+            return Err(Error::binary(offset, "invalid length marker"));
+        }
+
+        let len = 1usize << (marker & 0x0f);
+        let bytes = self.slice(offset + 2, len)?;
+
+        Ok((read_uint(bytes) as usize, offset + 2 + len))
+    }
+
+    fn read_refs(&self, start: usize, count: usize) -> Result<Vec<usize>, Error> {
+        let len = count
+            .checked_mul(self.ref_size)
+            .ok_or_else(|| Error::binary(start, "container is too large"))?;
+
+        Ok(self
+            .slice(start, len)?
+            .chunks_exact(self.ref_size)
+            .map(|bytes| read_uint(bytes) as usize)
+            .collect())
+    }
+}
+
+fn read_uint(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0u64, |value, byte| (value << 8) | u64::from(*byte))
+}
+
+/// Reads an integer object. Integers of up to 4 bytes are unsigned, 8-byte integers are signed
+/// and 16-byte integers are signed 128-bit values that must fit into an `i64`.
+fn read_int(bytes: &[u8], offset: usize) -> Result<i64, Error> {
+    match bytes.len() {
+        1 | 2 | 4 | 8 => Ok(read_uint(bytes) as i64),
+        16 => {
+            let value = i128::from_be_bytes(bytes.try_into().unwrap());
+            i64::try_from(value).map_err(|_| Error::binary(offset, "integer is out of range"))
+        }
+        _ => Err(Error::binary(offset, "invalid integer width")),
+    }
+}
+
+pub(crate) fn write(mut writer: impl Write, value: &Value) -> Result<(), Error> {
+    let mut objects = Vec::new();
+    flatten(value, &mut objects);
+
+    let ref_size = byte_width(objects.len() as u64);
+
+    let mut bytes = MAGIC.to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+
+    for object in &objects {
+        offsets.push(bytes.len() as u64);
+        write_object(&mut bytes, object, ref_size);
+    }
+
+    let offset_table_start = bytes.len() as u64;
+    let offset_size = byte_width(offset_table_start);
+
+    for offset in offsets {
+        write_uint(&mut bytes, offset, offset_size);
+    }
+
+    bytes.extend_from_slice(&[0; 6]);
+    bytes.push(offset_size as u8);
+    bytes.push(ref_size as u8);
+    bytes.extend_from_slice(&(objects.len() as u64).to_be_bytes());
+    bytes.extend_from_slice(&0u64.to_be_bytes());
+    bytes.extend_from_slice(&offset_table_start.to_be_bytes());
+
+    writer.write_all(&bytes)?;
+
+    Ok(())
+}
+
+/// An object in the flattened object table. Containers refer to their children by index.
+enum Object<'a> {
+    Leaf(&'a Value),
+    Key(&'a str),
+    Array(Vec<usize>),
+    Dictionary(Vec<usize>, Vec<usize>),
+}
+
+/// Appends `value` and its descendants to `objects` in depth-first order, returning the index of
+/// `value`. The root always ends up at index 0.
+fn flatten<'a>(value: &'a Value, objects: &mut Vec<Object<'a>>) -> usize {
+    let index = objects.len();
+
+    match value {
+        Value::Array(array) => {
+            objects.push(Object::Array(Vec::new()));
+            let refs = array.iter().map(|child| flatten(child, objects)).collect();
+            objects[index] = Object::Array(refs);
+        }
+        Value::Dictionary(dictionary) => {
+            objects.push(Object::Dictionary(Vec::new(), Vec::new()));
+
+            let key_refs = dictionary
+                .keys()
+                .map(|key| {
+                    objects.push(Object::Key(key));
+                    objects.len() - 1
+                })
+                .collect();
+            let value_refs = dictionary
+                .values()
+                .map(|child| flatten(child, objects))
+                .collect();
+
+            objects[index] = Object::Dictionary(key_refs, value_refs);
+        }
+        _ => objects.push(Object::Leaf(value)),
+    }
+
+    index
+}
+
+fn write_object(bytes: &mut Vec<u8>, object: &Object, ref_size: usize) {
+    match object {
+        Object::Key(key) => write_string(bytes, key),
+        Object::Array(refs) => {
+            write_marker(bytes, 0xa, refs.len());
+            for child in refs {
+                write_uint(bytes, *child as u64, ref_size);
+            }
+        }
+        Object::Dictionary(key_refs, value_refs) => {
+            write_marker(bytes, 0xd, key_refs.len());
+            for child in key_refs.iter().chain(value_refs) {
+                write_uint(bytes, *child as u64, ref_size);
+            }
+        }
+        Object::Leaf(value) => match value {
+            Value::Boolean(false) => bytes.push(0x08),
+            Value::Boolean(true) => bytes.push(0x09),
+            Value::Integer(value) => write_int(bytes, *value),
+            Value::Real(value) => {
+                bytes.push(0x23);
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+            Value::Date(date) => {
+                bytes.push(0x33);
+                bytes.extend_from_slice(&reference_interval_from_system_time(*date).to_be_bytes());
+            }
+            Value::Data(data) => {
+                write_marker(bytes, 0x4, data.len());
+                bytes.extend_from_slice(data);
+            }
+            Value::String(string) => write_string(bytes, string),
+            Value::Uid(uid) => {
+                let width = byte_width(*uid);
+                bytes.push(0x80 | (width as u8 - 1));
+                write_uint(bytes, *uid, width);
+            }
+            Value::Array(_) | Value::Dictionary(_) => unreachable!("containers are flattened"),
+        },
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    if string.is_ascii() {
+        write_marker(bytes, 0x5, string.len());
+        bytes.extend_from_slice(string.as_bytes());
+    } else {
+        let units = string.encode_utf16().collect::<Vec<_>>();
+        write_marker(bytes, 0x6, units.len());
+        for unit in units {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        }
+    }
+}
+
+/// Writes a marker byte for a variable-length object, spilling the length into a following
+/// integer object when it doesn't fit in the low nibble.
+fn write_marker(bytes: &mut Vec<u8>, kind: u8, len: usize) {
+    if len < 0x0f {
+        bytes.push(kind << 4 | len as u8);
+    } else {
+        bytes.push(kind << 4 | 0x0f);
+        write_int(bytes, len as i64);
+    }
+}
+
+fn write_int(bytes: &mut Vec<u8>, value: i64) {
+    if value < 0 {
+        bytes.push(0x13);
+        bytes.extend_from_slice(&value.to_be_bytes());
+        return;
+    }
+
+    let width = byte_width(value as u64);
+    bytes.push(0x10 | width.trailing_zeros() as u8);
+    write_uint(bytes, value as u64, width);
+}
+
+fn write_uint(bytes: &mut Vec<u8>, value: u64, width: usize) {
+    bytes.extend_from_slice(&value.to_be_bytes()[8 - width..]);
+}
+
+/// The number of bytes needed to store `value`, rounded up to 1, 2, 4 or 8.
+fn byte_width(value: u64) -> usize {
+    let bytes = (8 - value.leading_zeros() as usize / 8).max(1);
+    bytes.next_power_of_two()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plist::Format;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/plist/",
+                $name
+            ))
+        };
+    }
+
+    #[test]
+    fn reads_info_plist_like_its_xml_twin() {
+        let binary = read(fixture!("Info.bplist")).unwrap();
+        let xml = crate::plist::from_bytes(fixture!("Info.plist")).unwrap();
+
+        assert_eq!(Format::detect(fixture!("Info.bplist")), Format::Binary);
+        assert_eq!(binary, xml);
+    }
+
+    #[test]
+    fn reads_keyed_archive_uids() {
+        let archive = read(fixture!("KeyedArchive.bplist")).unwrap();
+
+        assert_eq!(archive.get_string("$archiver"), Some("NSKeyedArchiver"));
+        assert_eq!(
+            archive.get("$top").and_then(|top| top.get("root")),
+            Some(&Value::Uid(1))
+        );
+
+        let objects = archive.get("$objects").and_then(Value::as_array).unwrap();
+        assert_eq!(objects[1].get("$class"), Some(&Value::Uid(4)));
+        assert_eq!(
+            objects[1].get("NS.keys"),
+            Some(&Value::Array(vec![Value::Uid(2)]))
+        );
+        assert_eq!(
+            objects[3].get("NS.time").and_then(Value::as_real),
+            Some(718_000_000.5)
+        );
+    }
+
+    #[test]
+    fn round_trips_fixtures() {
+        for bytes in [
+            &fixture!("Info.bplist")[..],
+            &fixture!("KeyedArchive.bplist")[..],
+        ] {
+            let value = read(bytes).unwrap();
+
+            let mut written = Vec::new();
+            write(&mut written, &value).unwrap();
+            assert_eq!(read(&written).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn round_trips_wide_values() {
+        let value = Value::Array(vec![
+            Value::Integer(i64::MIN),
+            Value::Integer(i64::MAX),
+            Value::Integer(-1),
+            Value::Uid(u64::MAX),
+            Value::String("ünïcödé".to_string()),
+            Value::String("x".repeat(300)),
+            Value::Data(vec![0; 70_000]),
+        ]);
+
+        let mut written = Vec::new();
+        write(&mut written, &value).unwrap();
+        assert_eq!(read(&written).unwrap(), value);
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        assert!(matches!(
+            read(fixture!("truncated.bplist")),
+            Err(Error::InvalidBinary { .. })
+        ));
+
+        let archive = fixture!("KeyedArchive.bplist");
+        for len in [
+            0,
+            MAGIC.len(),
+            MAGIC.len() + TRAILER_LEN - 1,
+            archive.len() / 2,
+        ] {
+            assert!(read(&archive[..len]).is_err(), "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        match read(fixture!("cyclic.bplist")) {
+            Err(Error::InvalidBinary { message, .. }) => assert!(message.contains("cycle")),
+            result => panic!("expected a cycle error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reads_shared_references() {
+        let leaf = Value::Array(vec![Value::Integer(0), Value::Integer(0)]);
+        let middle = Value::Array(vec![leaf.clone(), leaf]);
+
+        assert_eq!(
+            read(fixture!("shared.bplist")).unwrap(),
+            Value::Array(vec![middle.clone(), middle])
+        );
+    }
+
+    #[test]
+    fn rejects_exponential_shared_references() {
+        // 60 arrays that each refer to the next one twice.
+        match read(fixture!("shared-dag.bplist")) {
+            Err(Error::InvalidBinary { message, .. }) => {
+                assert!(message.contains("too many objects"))
+            }
+            result => panic!("expected an expansion error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn rejects_offsets_out_of_bounds() {
+        match read(fixture!("bad-offset.bplist")) {
+            Err(Error::InvalidBinary { message, .. }) => {
+                assert!(message.contains("out of bounds"))
+            }
+            result => panic!("expected an offset error, got {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Conversions between [`Value`] and Foundation objects.
+
+use cocoa::base::{BOOL, YES};
+use objc::runtime::Class;
+
+use super::{Dictionary, Value};
+use crate::date::{reference_interval_from_system_time, system_time_from_reference_interval};
+use crate::prelude::*;
+
+unsafe fn is_kind_of(ptr: Id, class: &Class) -> bool {
+    let result: BOOL = msg_send![ptr, isKindOfClass: class];
+    result == YES
+}
+
+unsafe fn dictionary_to_id(dictionary: &Dictionary) -> Id {
+    let capacity = dictionary.len();
+    let result: Id = msg_send![class!(NSMutableDictionary), dictionaryWithCapacity: capacity];
+
+    for (key, value) in dictionary {
+        let key = Value::String(key.clone()).to_id();
+        let value = value.to_id();
+        let _: () = msg_send![result, setObject:value forKey:key];
+    }
+
+    result
+}
+
+impl Value {
+    /// Converts a Foundation object graph made of `NSString`, `NSNumber`, `NSDate`, `NSData`,
+    /// `NSArray` and `NSDictionary` objects to a `Value`.
+    ///
+    /// Returns `None` if `ptr` is nil or the graph contains anything else.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be nil or point to a valid Objective-C object.
+    pub unsafe fn from_id(ptr: Id) -> Option<Value> {
+        if ptr.is_null() {
+            return None;
+        }
+
+        if is_kind_of(ptr, class!(NSString)) {
+            return Some(Value::String(NS_String::from_ptr(ptr)?.to_string()));
+        }
+
+        if is_kind_of(ptr, class!(NSNumber)) {
+            return Some(Number::from_ptr(ptr)?.to_plist());
+        }
+
+        if is_kind_of(ptr, class!(NSDate)) {
+            let seconds: f64 = msg_send![ptr, timeIntervalSinceReferenceDate];
+            return system_time_from_reference_interval(seconds).map(Value::Date);
+        }
+
+        if is_kind_of(ptr, class!(NSData)) {
+            return Some(Value::Data(Data::from_ptr(ptr)?.to_vec()));
+        }
+
+        if is_kind_of(ptr, class!(NSArray)) {
+            return Array::from_ptr(ptr)?.to_plist().map(Value::Array);
+        }
+
+        if is_kind_of(ptr, class!(NSDictionary)) {
+            let mut dictionary = Dictionary::new();
+
+            let keys: Id = msg_send![ptr, allKeys];
+            let count: usize = msg_send![keys, count];
+
+            for i in 0..count {
+                let key: Id = msg_send![keys, objectAtIndex: i];
+                let value: Id = msg_send![ptr, objectForKey: key];
+
+                match Value::from_id(key)? {
+                    Value::String(key) => dictionary.insert(key, Value::from_id(value)?),
+                    _ => return None,
+                };
+            }
+
+            return Some(Value::Dictionary(dictionary));
+        }
+
+        None
+    }
+
+    /// Converts the value to an autoreleased Foundation object.
+    ///
+    /// [`Value::Uid`] has no public Foundation counterpart and becomes an `NSNumber`.
+    pub fn to_id(&self) -> Id {
+        unsafe {
+            match self {
+                Value::Array(array) => {
+                    let capacity = array.len();
+                    let result: Id = msg_send![class!(NSMutableArray), arrayWithCapacity: capacity];
+
+                    for value in array {
+                        let value = value.to_id();
+                        let _: () = msg_send![result, addObject: value];
+                    }
+
+                    result
+                }
+                Value::Dictionary(dictionary) => dictionary_to_id(dictionary),
+                Value::Boolean(value) => {
+                    let value: BOOL = (*value).into();
+                    msg_send![class!(NSNumber), numberWithBool: value]
+                }
+                Value::Data(data) => {
+                    msg_send![class!(NSData), dataWithBytes:data.as_ptr() length:data.len()]
+                }
+                Value::Date(date) => {
+                    let seconds = reference_interval_from_system_time(*date);
+                    msg_send![class!(NSDate), dateWithTimeIntervalSinceReferenceDate: seconds]
+                }
+                Value::Real(value) => msg_send![class!(NSNumber), numberWithDouble: *value],
+                Value::Integer(value) => msg_send![class!(NSNumber), numberWithLongLong: *value],
+                Value::String(string) => {
+                    let string = NS_String::from(string).ptr();
+                    msg_send![string, autorelease]
+                }
+                Value::Uid(uid) => msg_send![class!(NSNumber), numberWithUnsignedLongLong: *uid],
+            }
+        }
+    }
+}
+
+impl From<&NS_String> for Value {
+    fn from(string: &NS_String) -> Self {
+        Value::String(string.to_string())
+    }
+}
+
+impl Dict {
+    /// Converts the dictionary to a property list [`Dictionary`].
+    ///
+    /// Entries whose key isn't a string, or whose value isn't a property list type, are skipped.
+    pub fn to_plist(&self) -> Dictionary {
+        unsafe {
+            let mut dictionary = Dictionary::new();
+
+            let keys: Id = msg_send![self.ptr(), allKeys];
+            let count: usize = msg_send![keys, count];
+
+            for i in 0..count {
+                let key: Id = msg_send![keys, objectAtIndex: i];
+                let value: Id = msg_send![self.ptr(), objectForKey: key];
+
+                if let (Some(Value::String(key)), Some(value)) =
+                    (Value::from_id(key), Value::from_id(value))
+                {
+                    dictionary.insert(key, value);
+                }
+            }
+
+            dictionary
+        }
+    }
+
+    /// Creates an autoreleased `NSDictionary` from a property list [`Dictionary`].
+    pub fn from_plist(dictionary: &Dictionary) -> Dict {
+        unsafe { Dict::from_ptr(dictionary_to_id(dictionary)).unwrap() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Dict {
+    /// Converts any `Serialize` type that serializes to a map, such as a struct, into an
+    /// autoreleased `NSDictionary`.
+    ///
+    /// The value is serialized straight into Foundation objects, following the same mapping as
+    /// [`super::to_value`].
+    pub fn from_serializable<T: serde::Serialize + ?Sized>(
+        value: &T,
+    ) -> Result<Dict, super::Error> {
+        let ptr = super::cocoa_ser::to_id(value)?;
+
+        unsafe {
+            if is_kind_of(ptr, class!(NSDictionary)) {
+                return Ok(Dict::from_ptr(ptr).unwrap());
+            }
+        }
+
+        Err(super::Error::Serde(
+            "only maps and structs can be converted to an NSDictionary".to_string(),
+        ))
+    }
+
+    /// Deserializes the dictionary's property list entries into `T`.
+    pub fn deserialize_as<T: serde::de::DeserializeOwned>(&self) -> Result<T, super::Error> {
+        super::from_value(Value::Dictionary(self.to_plist()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Dict {
+    /// Serializes the dictionary's property list entries, skipping any others.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_plist().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Dict {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let dictionary = Dictionary::deserialize(deserializer)?;
+        Ok(Dict::from_plist(&dictionary))
+    }
+}
@@ -0,0 +1,386 @@
+//! Serialization of Rust values directly into Foundation objects.
+
+use cocoa::base::BOOL;
+use serde::ser::{self, Serialize};
+
+use super::ser::key_from_value;
+use super::{to_value, Error};
+use crate::prelude::*;
+
+/// Converts any `Serialize` type to an autoreleased Foundation object.
+///
+/// Rust types map onto `NSDictionary`, `NSArray`, `NSString`, `NSNumber` and `NSData` objects
+/// the same way [`to_value`] maps them onto [`Value`](super::Value), without building the
+/// intermediate tree.
+pub(super) fn to_id<T: Serialize + ?Sized>(value: &T) -> Result<Id, Error> {
+    value
+        .serialize(FoundationSerializer)?
+        .ok_or_else(|| Error::Serde("a property list value can't be None".to_string()))
+}
+
+/// Produces `None` for values that property lists can only represent by omission.
+struct FoundationSerializer;
+
+fn string_to_id(string: &str) -> Id {
+    unsafe {
+        let string = NS_String::from(string).ptr();
+        msg_send![string, autorelease]
+    }
+}
+
+fn variant_dictionary(variant: &'static str, value: Id) -> Option<Id> {
+    unsafe {
+        let key = string_to_id(variant);
+        Some(msg_send![class!(NSDictionary), dictionaryWithObject:value forKey:key])
+    }
+}
+
+fn mutable_array(capacity: usize) -> Id {
+    unsafe { msg_send![class!(NSMutableArray), arrayWithCapacity: capacity] }
+}
+
+fn mutable_dictionary(capacity: usize) -> Id {
+    unsafe { msg_send![class!(NSMutableDictionary), dictionaryWithCapacity: capacity] }
+}
+
+fn add_object(array: Id, value: Id) {
+    unsafe {
+        let _: () = msg_send![array, addObject: value];
+    }
+}
+
+fn set_object(dictionary: Id, key: Id, value: Id) {
+    unsafe {
+        let _: () = msg_send![dictionary, setObject:value forKey:key];
+    }
+}
+
+impl ser::Serializer for FoundationSerializer {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeDictionary;
+    type SerializeStruct = SerializeDictionary;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok, Error> {
+        let value: BOOL = value.into();
+        Ok(Some(unsafe {
+            msg_send![class!(NSNumber), numberWithBool: value]
+        }))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok, Error> {
+        Ok(Some(unsafe {
+            msg_send![class!(NSNumber), numberWithLongLong: value]
+        }))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok, Error> {
+        let value = i64::try_from(value)
+            .map_err(|_| Error::Serde(format!("{value} is too large for a property list")))?;
+
+        self.serialize_i64(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Self::Ok, Error> {
+        self.serialize_f64(value.into())
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Self::Ok, Error> {
+        Ok(Some(unsafe {
+            msg_send![class!(NSNumber), numberWithDouble: value]
+        }))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Self::Ok, Error> {
+        self.serialize_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Error> {
+        Ok(Some(string_to_id(value)))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Error> {
+        Ok(Some(unsafe {
+            msg_send![class!(NSData), dataWithBytes:value.as_ptr() length:value.len()]
+        }))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Err(Error::Serde(
+            "property lists can't represent `()`".to_string(),
+        ))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Error> {
+        Err(Error::Serde(format!(
+            "property lists can't represent the unit struct `{name}`"
+        )))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        Ok(variant_dictionary(variant, to_id(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(SerializeArray {
+            array: mutable_array(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Ok(SerializeTupleVariant {
+            variant,
+            array: mutable_array(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(SerializeDictionary {
+            dictionary: mutable_dictionary(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Ok(SerializeStructVariant {
+            variant,
+            dictionary: mutable_dictionary(len),
+        })
+    }
+}
+
+struct SerializeArray {
+    array: Id,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        add_object(self.array, to_id(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(self.array))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeTupleVariant {
+    variant: &'static str,
+    array: Id,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        add_object(self.array, to_id(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(variant_dictionary(self.variant, self.array))
+    }
+}
+
+struct SerializeDictionary {
+    dictionary: Id,
+    next_key: Option<Id>,
+}
+
+impl ser::SerializeMap for SerializeDictionary {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        // Keys are scalars, so going through a `Value` here copies nothing of note.
+        self.next_key = Some(string_to_id(&key_from_value(to_value(key)?)?));
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| Error::Serde("serialize_value called before serialize_key".into()))?;
+
+        if let Some(value) = value.serialize(FoundationSerializer)? {
+            set_object(self.dictionary, key, value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(self.dictionary))
+    }
+}
+
+impl ser::SerializeStruct for SerializeDictionary {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if let Some(value) = value.serialize(FoundationSerializer)? {
+            set_object(self.dictionary, string_to_id(key), value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+struct SerializeStructVariant {
+    variant: &'static str,
+    dictionary: Id,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = Option<Id>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if let Some(value) = value.serialize(FoundationSerializer)? {
+            set_object(self.dictionary, string_to_id(key), value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(variant_dictionary(self.variant, self.dictionary))
+    }
+}
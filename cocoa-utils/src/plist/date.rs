@@ -0,0 +1,200 @@
+//! ISO 8601 conversions for dates in XML property lists.
+//!
+//! Binary property lists store dates relative to the reference date instead, see [`crate::date`].
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Formats `time` as `YYYY-MM-DDTHH:MM:SSZ`, truncating any fractional seconds.
+pub(crate) fn format_iso8601(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(error) => {
+            let duration = error.duration();
+            let seconds = -(duration.as_secs() as i64);
+
+            if duration.subsec_nanos() > 0 {
+                seconds - 1
+            } else {
+                seconds
+            }
+        }
+    };
+
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let time_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// Parses a `YYYY-MM-DDTHH:MM:SS[.fff]Z` timestamp.
+pub(crate) fn parse_iso8601(text: &str) -> Option<SystemTime> {
+    let text = text.trim().strip_suffix('Z')?;
+    let (date, time) = text.split_once('T')?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = parse_digits(date_parts.next()?)?;
+    let month: u32 = parse_digits(date_parts.next()?)?;
+    let day: u32 = parse_digits(date_parts.next()?)?;
+
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (time, None),
+    };
+
+    let mut time_parts = time.splitn(3, ':');
+    let hour: i64 = parse_digits(time_parts.next()?)?;
+    let minute: i64 = parse_digits(time_parts.next()?)?;
+    let second: i64 = parse_digits(time_parts.next()?)?;
+
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let nanos = match fraction {
+        Some(fraction) if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) => {
+            let digits = &fraction[..fraction.len().min(9)];
+            let scale = 10u32.pow(9 - digits.len() as u32);
+            digits.parse::<u32>().ok()? * scale
+        }
+        Some(_) => return None,
+        None => 0,
+    };
+
+    let seconds = days_from_civil(year, month, day)?
+        .checked_mul(SECONDS_PER_DAY)?
+        .checked_add(hour * 3600 + minute * 60 + second)?;
+
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::new(seconds as u64, nanos))
+    } else {
+        UNIX_EPOCH
+            .checked_sub(Duration::from_secs(seconds.unsigned_abs()))?
+            .checked_add(Duration::from_nanos(nanos.into()))
+    }
+}
+
+/// Parses a field made only of ASCII digits, so signs and whitespace are rejected.
+fn parse_digits<T: std::str::FromStr>(text: &str) -> Option<T> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    text.parse().ok()
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month as u32, day as u32)
+}
+
+/// Converts a proleptic Gregorian date to days since 1970-01-01, or `None` if that overflows.
+fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era.checked_mul(146_097)?
+        .checked_add(day_of_era)?
+        .checked_sub(719_468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_iso8601() {
+        for seconds in [
+            0i64,
+            1,
+            978_307_200,
+            1_700_000_000,
+            -1,
+            -86_401,
+            -2_208_988_800,
+        ] {
+            let time = if seconds >= 0 {
+                UNIX_EPOCH + Duration::from_secs(seconds as u64)
+            } else {
+                UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+            };
+
+            assert_eq!(parse_iso8601(&format_iso8601(time)), Some(time));
+        }
+
+        assert_eq!(
+            format_iso8601(UNIX_EPOCH + Duration::from_secs(978_307_200)),
+            "2001-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn parses_fractional_seconds() {
+        assert_eq!(
+            parse_iso8601("1970-01-01T00:00:01.25Z"),
+            Some(UNIX_EPOCH + Duration::from_millis(1250))
+        );
+        assert_eq!(
+            parse_iso8601("1969-12-31T23:59:59.5Z"),
+            Some(UNIX_EPOCH - Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_fields() {
+        for text in [
+            "2023-13-01T00:00:00Z",
+            "2023-01-32T00:00:00Z",
+            "2023-01-01T24:00:00Z",
+            "2023-01-01T-1:00:00Z",
+            "2023-01-01T00:-1:00Z",
+            "2023-01-01T00:00:-1Z",
+            "2023-01-01T00:00:+1Z",
+            "2023-01-01T00:00:00",
+            "2023-01-01T00:00:00.Z",
+        ] {
+            assert_eq!(parse_iso8601(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_years() {
+        assert_eq!(parse_iso8601("9223372036854775807-01-01T00:00:00Z"), None);
+        assert_eq!(parse_iso8601("99999999999999-01-01T00:00:00Z"), None);
+    }
+}
@@ -0,0 +1,294 @@
+//! Deserialization of Rust values from [`Value`] trees.
+
+use std::fmt;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{
+    self, Deserialize, DeserializeOwned, DeserializeSeed, IntoDeserializer, Unexpected, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use super::date::format_iso8601;
+use super::{Dictionary, Error, Value};
+
+/// Converts a property list [`Value`] to any `Deserialize` type.
+///
+/// This is the inverse of [`to_value`](super::to_value). Dates are presented to the target type
+/// as ISO 8601 strings.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    T::deserialize(value)
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a property list value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+        i64::try_from(value)
+            .map(Value::Integer)
+            .map_err(|_| E::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+        Ok(Value::Real(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Value, E> {
+        Ok(Value::Data(value.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Data(value))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(value) = seq.next_element()? {
+            array.push(value);
+        }
+
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut dictionary = Dictionary::new();
+
+        while let Some((key, value)) = map.next_entry()? {
+            dictionary.insert(key, value);
+        }
+
+        Ok(Value::Dictionary(dictionary))
+    }
+}
+
+impl Value {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Value::Array(_) => Unexpected::Seq,
+            Value::Dictionary(_) => Unexpected::Map,
+            Value::Boolean(value) => Unexpected::Bool(*value),
+            Value::Data(data) => Unexpected::Bytes(data),
+            Value::Date(_) => Unexpected::Other("date"),
+            Value::Real(value) => Unexpected::Float(*value),
+            Value::Integer(value) => Unexpected::Signed(*value),
+            Value::String(string) => Unexpected::Str(string),
+            Value::Uid(_) => Unexpected::Other("UID"),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Value::Array(array) => {
+                let mut seq = SeqDeserializer::new(array.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+
+                Ok(value)
+            }
+            Value::Dictionary(dictionary) => {
+                let mut map = MapDeserializer::new(dictionary.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+
+                Ok(value)
+            }
+            Value::Boolean(value) => visitor.visit_bool(value),
+            Value::Data(data) => visitor.visit_byte_buf(data),
+            Value::Date(date) => visitor.visit_string(format_iso8601(date)),
+            Value::Real(value) => visitor.visit_f64(value),
+            Value::Integer(value) => visitor.visit_i64(value),
+            Value::String(string) => visitor.visit_string(string),
+            Value::Uid(uid) => visitor.visit_u64(uid),
+        }
+    }
+
+    /// Property lists have no null, so any value that is present is `Some`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Accepts a string for unit variants, or a single-entry dictionary keyed by the variant name.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Dictionary(dictionary) if dictionary.len() == 1 => {
+                let (variant, value) = dictionary.into_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            other => Err(de::Error::invalid_type(
+                other.unexpected(),
+                &"a string or a single-entry dictionary",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, VariantDeserializer), Error> {
+        let variant: StringDeserializer<Error> = self.variant.into_deserializer();
+        let variant = seed.deserialize(variant)?;
+
+        Ok((variant, VariantDeserializer(self.value)))
+    }
+}
+
+struct VariantDeserializer(Value);
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(de::Error::invalid_type(
+            self.0.unexpected(),
+            &"unit variant",
+        ))
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, Error> {
+        seed.deserialize(self.0)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self.0, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self.0, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Mode {
+        Off,
+        Level(u8),
+    }
+
+    #[test]
+    fn presents_dates_as_iso8601() {
+        let date = Value::Date(crate::reference_date());
+
+        assert_eq!(from_value::<String>(date).unwrap(), "2001-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn reads_enums_and_options() {
+        assert_eq!(
+            from_value::<Mode>(Value::String("Off".to_string())).unwrap(),
+            Mode::Off
+        );
+
+        let mut level = Dictionary::new();
+        level.insert("Level".to_string(), Value::Integer(2));
+        assert_eq!(
+            from_value::<Mode>(Value::Dictionary(level)).unwrap(),
+            Mode::Level(2)
+        );
+
+        assert!(from_value::<Mode>(Value::Integer(0)).is_err());
+        assert_eq!(
+            from_value::<Option<i64>>(Value::Integer(1)).unwrap(),
+            Some(1)
+        );
+        assert_eq!(from_value::<u64>(Value::Uid(9)).unwrap(), 9);
+    }
+
+    #[test]
+    fn rejects_out_of_range_integers() {
+        assert!(matches!(
+            from_value::<u8>(Value::Integer(256)),
+            Err(Error::Serde(_))
+        ));
+        assert!(matches!(
+            from_value::<u32>(Value::Integer(-1)),
+            Err(Error::Serde(_))
+        ));
+        assert!(matches!(
+            from_value::<Value>(Value::Uid(u64::MAX)),
+            Err(Error::Serde(_))
+        ));
+    }
+}
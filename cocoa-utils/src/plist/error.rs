@@ -0,0 +1,77 @@
+use std::fmt;
+
+/// An error produced while reading or writing a property list.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying reader or writer failed.
+    Io(std::io::Error),
+    /// The XML document is malformed, or isn't a property list.
+    InvalidXml { position: usize, message: String },
+    /// The binary document is truncated or otherwise malformed.
+    InvalidBinary { offset: usize, message: String },
+    /// A Rust value couldn't be converted to or from a [`Value`](super::Value).
+    Serde(String),
+}
+
+impl Error {
+    pub(crate) fn xml(position: usize, message: impl Into<String>) -> Error {
+        Error::InvalidXml {
+            position,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn binary(offset: usize, message: impl Into<String>) -> Error {
+        Error::InvalidBinary {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{error}"),
+            Error::InvalidXml { position, message } => {
+                write!(f, "invalid XML property list at byte {position}: {message}")
+            }
+            Error::InvalidBinary { offset, message } => {
+                write!(
+                    f,
+                    "invalid binary property list at offset {offset}: {message}"
+                )
+            }
+            Error::Serde(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Serde(message.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Serde(message.to_string())
+    }
+}
@@ -0,0 +1,106 @@
+//! Pure-Rust reader and writer for property lists.
+//!
+//! Both the XML (`<plist version="1.0">`) and binary (`bplist00`) formats are supported. Parsed
+//! documents become a [`Value`] tree, which can be written back out in either format. Nothing in
+//! this module depends on Foundation, so it works on every platform.
+//!
+//! On macOS, [`Value`] can also be converted to and from Foundation objects such as [`Dict`] and
+//! [`NS_String`].
+//!
+//! With the `serde` feature, [`to_value`] and [`from_value`] convert between [`Value`] and any
+//! `Serialize`/`Deserialize` type, and [`Value`] itself implements both traits.
+//!
+//! [`Dict`]: crate::Dict
+//! [`NS_String`]: crate::NS_String
+
+mod binary;
+mod date;
+mod error;
+mod value;
+mod xml;
+
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+mod ser;
+
+#[cfg(target_os = "macos")]
+mod cocoa;
+#[cfg(all(target_os = "macos", feature = "serde"))]
+mod cocoa_ser;
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+pub use error::*;
+pub use value::*;
+
+#[cfg(feature = "serde")]
+pub use de::from_value;
+#[cfg(feature = "serde")]
+pub use ser::to_value;
+
+/// The on-disk encoding of a property list.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    Xml,
+    Binary,
+}
+
+impl Format {
+    /// Detects the format of an encoded property list from its leading bytes.
+    pub fn detect(bytes: &[u8]) -> Format {
+        if bytes.starts_with(binary::MAGIC) {
+            Format::Binary
+        } else {
+            Format::Xml
+        }
+    }
+}
+
+/// Parses a property list in either format, detecting which one from its contents.
+pub fn from_bytes(bytes: &[u8]) -> Result<Value, Error> {
+    match Format::detect(bytes) {
+        Format::Binary => binary::read(bytes),
+        Format::Xml => xml::read(bytes),
+    }
+}
+
+/// Reads and parses a property list from `reader`.
+pub fn from_reader(mut reader: impl Read) -> Result<Value, Error> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    from_bytes(&bytes)
+}
+
+/// Reads and parses the property list at `path`, such as an app bundle's `Info.plist`.
+pub fn from_file(path: impl AsRef<Path>) -> Result<Value, Error> {
+    let bytes = std::fs::read(path)?;
+
+    from_bytes(&bytes)
+}
+
+/// Encodes `value` as a property list in the given format.
+pub fn to_bytes(value: &Value, format: Format) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    to_writer(&mut bytes, value, format)?;
+
+    Ok(bytes)
+}
+
+/// Encodes `value` as a property list in the given format and writes it to `writer`.
+pub fn to_writer(writer: impl Write, value: &Value, format: Format) -> Result<(), Error> {
+    match format {
+        Format::Xml => xml::write(writer, value),
+        Format::Binary => binary::write(writer, value),
+    }
+}
+
+/// Encodes `value` as a property list in the given format and writes it to `path`.
+pub fn to_file(path: impl AsRef<Path>, value: &Value, format: Format) -> Result<(), Error> {
+    let bytes = to_bytes(value, format)?;
+    std::fs::write(path, bytes)?;
+
+    Ok(())
+}
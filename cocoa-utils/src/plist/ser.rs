@@ -0,0 +1,514 @@
+//! Serialization of Rust values into [`Value`] trees.
+
+use serde::ser::{self, Serialize};
+
+use super::date::format_iso8601;
+use super::{Dictionary, Error, Value};
+
+/// Converts any `Serialize` type to a property list [`Value`].
+///
+/// Structs and maps become dictionaries, sequences and tuples become arrays, and unit enum
+/// variants become strings. Other enum variants become a single-entry dictionary keyed by the
+/// variant name.
+///
+/// Property lists have no null: `None` fields of structs and maps are left out, and `None` or
+/// `()` anywhere else is an error. Map keys must be strings or integers.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+    value
+        .serialize(ValueSerializer)?
+        .ok_or_else(|| Error::Serde("a property list value can't be None".to_string()))
+}
+
+/// Dates are serialized as ISO 8601 strings, and UIDs as unsigned integers.
+impl Serialize for Value {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Array(array) => array.serialize(serializer),
+            Value::Dictionary(dictionary) => dictionary.serialize(serializer),
+            Value::Boolean(value) => serializer.serialize_bool(*value),
+            Value::Data(data) => serializer.serialize_bytes(data),
+            Value::Date(date) => serializer.serialize_str(&format_iso8601(*date)),
+            Value::Real(value) => serializer.serialize_f64(*value),
+            Value::Integer(value) => serializer.serialize_i64(*value),
+            Value::String(string) => serializer.serialize_str(string),
+            Value::Uid(uid) => serializer.serialize_u64(*uid),
+        }
+    }
+}
+
+/// Produces `None` for values that property lists can only represent by omission.
+struct ValueSerializer;
+
+pub(super) fn key_from_value(key: Value) -> Result<String, Error> {
+    match key {
+        Value::String(key) => Ok(key),
+        Value::Integer(key) => Ok(key.to_string()),
+        _ => Err(Error::Serde(
+            "property list dictionary keys must be strings".to_string(),
+        )),
+    }
+}
+
+fn variant_dictionary(variant: &'static str, value: Value) -> Option<Value> {
+    let mut dictionary = Dictionary::new();
+    dictionary.insert(variant.to_string(), value);
+
+    Some(Value::Dictionary(dictionary))
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeDictionary;
+    type SerializeStruct = SerializeDictionary;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::Boolean(value)))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::Integer(value)))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok, Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok, Error> {
+        let value = i64::try_from(value)
+            .map_err(|_| Error::Serde(format!("{value} is too large for a property list")))?;
+
+        self.serialize_i64(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Self::Ok, Error> {
+        self.serialize_f64(value.into())
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::Real(value)))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::String(value.to_string())))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::String(value.to_string())))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::Data(value.to_vec())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Err(Error::Serde(
+            "property lists can't represent `()`".to_string(),
+        ))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Error> {
+        Err(Error::Serde(format!(
+            "property lists can't represent the unit struct `{name}`"
+        )))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        Ok(variant_dictionary(variant, to_value(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(SerializeArray {
+            array: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Ok(SerializeTupleVariant {
+            variant,
+            array: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(SerializeDictionary {
+            dictionary: Dictionary::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Ok(SerializeStructVariant {
+            variant,
+            dictionary: Dictionary::new(),
+        })
+    }
+}
+
+struct SerializeArray {
+    array: Vec<Value>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.array.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::Array(self.array)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeTupleVariant {
+    variant: &'static str,
+    array: Vec<Value>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.array.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(variant_dictionary(self.variant, Value::Array(self.array)))
+    }
+}
+
+struct SerializeDictionary {
+    dictionary: Dictionary,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeDictionary {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.next_key = Some(key_from_value(to_value(key)?)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| Error::Serde("serialize_value called before serialize_key".into()))?;
+
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.dictionary.insert(key, value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::Dictionary(self.dictionary)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeDictionary {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.dictionary.insert(key.to_string(), value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+struct SerializeStructVariant {
+    variant: &'static str,
+    dictionary: Dictionary,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.dictionary.insert(key.to_string(), value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(variant_dictionary(
+            self.variant,
+            Value::Dictionary(self.dictionary),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::plist::from_value;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Point(i32, i32),
+        Rect { width: u32, height: u32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Document {
+        name: String,
+        count: u32,
+        enabled: bool,
+        shapes: Vec<Shape>,
+        nickname: Option<String>,
+        note: Option<String>,
+        payload: Value,
+        nested: BTreeMap<String, BTreeMap<String, i64>>,
+    }
+
+    fn dictionary<const N: usize>(entries: [(&str, Value); N]) -> Value {
+        Value::Dictionary(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn round_trips_structs_and_enums() {
+        let document = Document {
+            name: "Notes".to_string(),
+            count: 3,
+            enabled: true,
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(1.5),
+                Shape::Point(-1, 2),
+                Shape::Rect {
+                    width: 4,
+                    height: 5,
+                },
+            ],
+            nickname: Some("n".to_string()),
+            note: None,
+            payload: Value::Data(vec![0, 1, 0xff]),
+            nested: BTreeMap::from([(
+                "outer".to_string(),
+                BTreeMap::from([("inner".to_string(), 7)]),
+            )]),
+        };
+
+        let value = to_value(&document).unwrap();
+
+        assert_eq!(
+            value,
+            dictionary([
+                ("name", Value::String("Notes".to_string())),
+                ("count", Value::Integer(3)),
+                ("enabled", Value::Boolean(true)),
+                (
+                    "shapes",
+                    Value::Array(vec![
+                        Value::String("Empty".to_string()),
+                        dictionary([("Circle", Value::Real(1.5))]),
+                        dictionary([(
+                            "Point",
+                            Value::Array(vec![Value::Integer(-1), Value::Integer(2)])
+                        )]),
+                        dictionary([(
+                            "Rect",
+                            dictionary([
+                                ("width", Value::Integer(4)),
+                                ("height", Value::Integer(5)),
+                            ])
+                        )]),
+                    ])
+                ),
+                ("nickname", Value::String("n".to_string())),
+                ("payload", Value::Data(vec![0, 1, 0xff])),
+                (
+                    "nested",
+                    dictionary([("outer", dictionary([("inner", Value::Integer(7))]))])
+                ),
+            ])
+        );
+        assert_eq!(from_value::<Document>(value).unwrap(), document);
+    }
+
+    #[test]
+    fn serializes_dates_as_iso8601() {
+        let date = Value::Date(crate::reference_date());
+
+        assert_eq!(
+            to_value(&date).unwrap(),
+            Value::String("2001-01-01T00:00:00Z".to_string())
+        );
+    }
+
+    #[test]
+    fn stringifies_integer_keys() {
+        assert_eq!(
+            to_value(&BTreeMap::from([(1, true)])).unwrap(),
+            dictionary([("1", Value::Boolean(true))])
+        );
+    }
+
+    #[test]
+    fn rejects_unrepresentable_values() {
+        assert!(matches!(
+            to_value(&BTreeMap::from([(true, 1)])),
+            Err(Error::Serde(_))
+        ));
+        assert!(matches!(
+            to_value(&BTreeMap::from([((1, 2), 1)])),
+            Err(Error::Serde(_))
+        ));
+        assert!(matches!(to_value(&u64::MAX), Err(Error::Serde(_))));
+        assert!(matches!(to_value(&None::<i32>), Err(Error::Serde(_))));
+        assert!(matches!(to_value(&()), Err(Error::Serde(_))));
+        assert!(matches!(to_value(&vec![None::<i32>]), Err(Error::Serde(_))));
+    }
+}
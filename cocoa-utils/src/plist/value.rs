@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+/// The key-value pairs of a property list `<dict>`.
+pub type Dictionary = BTreeMap<String, Value>;
+
+/// A node in a property list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Array(Vec<Value>),
+    Dictionary(Dictionary),
+    Boolean(bool),
+    Data(Vec<u8>),
+    Date(SystemTime),
+    Real(f64),
+    Integer(i64),
+    String(String),
+    /// A keyed-archiver object reference. Only representable in binary property lists.
+    Uid(u64),
+}
+
+impl Value {
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_dictionary(&self) -> Option<&Dictionary> {
+        match self {
+            Value::Dictionary(dictionary) => Some(dictionary),
+            _ => None,
+        }
+    }
+
+    pub fn as_boolean(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_data(&self) -> Option<&[u8]> {
+        match self {
+            Value::Data(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn as_date(&self) -> Option<SystemTime> {
+        match self {
+            Value::Date(date) => Some(*date),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a float. Integers are converted.
+    pub fn as_real(&self) -> Option<f64> {
+        match self {
+            Value::Real(value) => Some(*value),
+            Value::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_uid(&self) -> Option<u64> {
+        match self {
+            Value::Uid(uid) => Some(*uid),
+            _ => None,
+        }
+    }
+
+    /// Looks up `key` if this value is a dictionary.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_dictionary()
+            .and_then(|dictionary| dictionary.get(key))
+    }
+
+    /// Looks up `key` if this value is a dictionary, and returns it if it's a string.
+    ///
+    /// Handy for `Info.plist` lookups such as `CFBundleIdentifier`.
+    pub fn get_string(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_string)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Real(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Data(value)
+    }
+}
+
+impl From<SystemTime> for Value {
+    fn from(value: SystemTime) -> Self {
+        Value::Date(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Value::Array(value)
+    }
+}
+
+impl From<Dictionary> for Value {
+    fn from(value: Dictionary) -> Self {
+        Value::Dictionary(value)
+    }
+}
@@ -0,0 +1,644 @@
+//! The XML property list format.
+//!
+//! Only the subset of XML that property lists use is understood: elements, text, character and
+//! entity references, CDATA sections, comments, processing instructions and the doctype.
+
+use std::io::Write;
+
+use super::date::{format_iso8601, parse_iso8601};
+use super::{Dictionary, Error, Value};
+
+const HEADER: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+    "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+    "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+    "<plist version=\"1.0\">\n",
+);
+
+/// The dictionary key that CoreFoundation uses to write UIDs to XML.
+const UID_KEY: &str = "CF$UID";
+
+/// Nesting deeper than this is rejected instead of risking a stack overflow.
+const MAX_DEPTH: usize = 512;
+
+pub(crate) fn read(bytes: &[u8]) -> Result<Value, Error> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|error| Error::xml(error.valid_up_to(), "document is not valid UTF-8"))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    let mut parser = Parser {
+        tokenizer: Tokenizer { text, position: 0 },
+        depth: 0,
+    };
+
+    parser.parse_document()
+}
+
+enum Token<'a> {
+    Start(&'a str),
+    Empty(&'a str),
+    End(&'a str),
+    Text(String),
+}
+
+struct Tokenizer<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::xml(self.position, message)
+    }
+
+    /// Advances past `terminator`, returning everything before it.
+    fn take_until(&mut self, terminator: &str) -> Result<&'a str, Error> {
+        let rest = self.rest();
+        let end = rest
+            .find(terminator)
+            .ok_or_else(|| self.error(format!("expected `{terminator}`")))?;
+
+        self.position += end + terminator.len();
+
+        Ok(&rest[..end])
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, Error> {
+        loop {
+            let rest = self.rest();
+
+            if rest.is_empty() {
+                return Ok(None);
+            }
+
+            if rest.starts_with("<!--") {
+                self.position += "<!--".len();
+                self.take_until("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.position += "<![CDATA[".len();
+                let text = self.take_until("]]>")?;
+                return Ok(Some(Token::Text(text.to_string())));
+            } else if rest.starts_with("<?") {
+                self.take_until("?>")?;
+            } else if rest.starts_with("<!") {
+                self.skip_declaration()?;
+            } else if let Some(tag) = rest.strip_prefix("</") {
+                let end = tag
+                    .find('>')
+                    .ok_or_else(|| self.error("unterminated end tag"))?;
+                self.position += end + 3;
+                return Ok(Some(Token::End(tag[..end].trim())));
+            } else if rest.starts_with('<') {
+                return self.start_tag().map(Some);
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                let text = unescape(&rest[..end], self.position)?;
+                self.position += end;
+                return Ok(Some(Token::Text(text)));
+            }
+        }
+    }
+
+    /// Skips a `<!DOCTYPE ...>` declaration, including any bracketed internal subset.
+    fn skip_declaration(&mut self) -> Result<(), Error> {
+        let mut depth = 0usize;
+
+        for (index, char) in self.rest().char_indices() {
+            match char {
+                '[' => depth += 1,
+                ']' => depth = depth.saturating_sub(1),
+                '>' if depth == 0 => {
+                    self.position += index + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        Err(self.error("unterminated declaration"))
+    }
+
+    fn start_tag(&mut self) -> Result<Token<'a>, Error> {
+        let rest = self.rest();
+        let mut quote = None;
+
+        for (index, char) in rest.char_indices().skip(1) {
+            match (char, quote) {
+                ('"' | '\'', None) => quote = Some(char),
+                (c, Some(q)) if c == q => quote = None,
+                ('>', None) => {
+                    let inner = &rest[1..index];
+                    self.position += index + 1;
+
+                    let (inner, empty) = match inner.strip_suffix('/') {
+                        Some(inner) => (inner, true),
+                        None => (inner, false),
+                    };
+                    let name = inner.split_whitespace().next().unwrap_or("");
+
+                    if name.is_empty() {
+                        return Err(self.error("tag has no name"));
+                    }
+
+                    return Ok(if empty {
+                        Token::Empty(name)
+                    } else {
+                        Token::Start(name)
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Err(self.error("unterminated start tag"))
+    }
+}
+
+struct Parser<'a> {
+    tokenizer: Tokenizer<'a>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>) -> Error {
+        self.tokenizer.error(message)
+    }
+
+    /// Returns the next token that isn't whitespace-only text.
+    fn next_significant(&mut self) -> Result<Token<'a>, Error> {
+        loop {
+            match self.tokenizer.next_token()? {
+                Some(Token::Text(text)) if text.trim().is_empty() => continue,
+                Some(token) => return Ok(token),
+                None => return Err(self.error("unexpected end of document")),
+            }
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Value, Error> {
+        let value = match self.next_significant()? {
+            Token::Start("plist") => {
+                let token = self.next_significant()?;
+                let value = self.parse_value(token)?;
+
+                match self.next_significant()? {
+                    Token::End("plist") => value,
+                    _ => return Err(self.error("expected `</plist>`")),
+                }
+            }
+            token => self.parse_value(token)?,
+        };
+
+        loop {
+            match self.tokenizer.next_token()? {
+                None => return Ok(value),
+                Some(Token::Text(text)) if text.trim().is_empty() => continue,
+                Some(_) => return Err(self.error("unexpected content after the root value")),
+            }
+        }
+    }
+
+    fn parse_value(&mut self, token: Token<'a>) -> Result<Value, Error> {
+        let (name, empty) = match token {
+            Token::Start(name) => (name, false),
+            Token::Empty(name) => (name, true),
+            Token::End(name) => return Err(self.error(format!("unexpected `</{name}>`"))),
+            Token::Text(_) => return Err(self.error("unexpected text")),
+        };
+
+        let value = match name {
+            "dict" if empty => Value::Dictionary(Dictionary::new()),
+            "dict" => self.parse_dictionary()?,
+            "array" if empty => Value::Array(Vec::new()),
+            "array" => self.parse_array()?,
+            "true" | "false" => {
+                if !empty {
+                    self.expect_end(name)?;
+                }
+                Value::Boolean(name == "true")
+            }
+            "string" => Value::String(self.parse_text(name, empty)?),
+            "integer" => {
+                let text = self.parse_text(name, empty)?;
+                Value::Integer(
+                    parse_integer(text.trim())
+                        .ok_or_else(|| self.error(format!("invalid integer `{text}`")))?,
+                )
+            }
+            "real" => {
+                let text = self.parse_text(name, empty)?;
+                Value::Real(
+                    parse_real(text.trim())
+                        .ok_or_else(|| self.error(format!("invalid real `{text}`")))?,
+                )
+            }
+            "date" => {
+                let text = self.parse_text(name, empty)?;
+                Value::Date(
+                    parse_iso8601(&text)
+                        .ok_or_else(|| self.error(format!("invalid date `{text}`")))?,
+                )
+            }
+            "data" => {
+                let text = self.parse_text(name, empty)?;
+                Value::Data(base64_decode(&text).ok_or_else(|| self.error("invalid base64 data"))?)
+            }
+            _ => return Err(self.error(format!("unknown element `<{name}>`"))),
+        };
+
+        Ok(value)
+    }
+
+    fn enter(&mut self) -> Result<(), Error> {
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            Err(self.error("values are nested too deeply"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn parse_dictionary(&mut self) -> Result<Value, Error> {
+        self.enter()?;
+        let mut dictionary = Dictionary::new();
+
+        loop {
+            let key = match self.next_significant()? {
+                Token::End("dict") => break,
+                Token::Start("key") => self.parse_text("key", false)?,
+                Token::Empty("key") => String::new(),
+                _ => return Err(self.error("expected `<key>` or `</dict>`")),
+            };
+
+            let token = self.next_significant()?;
+            let value = self.parse_value(token)?;
+
+            dictionary.insert(key, value);
+        }
+
+        self.depth -= 1;
+
+        // CoreFoundation writes UIDs as `<dict><key>CF$UID</key><integer>..</integer></dict>`.
+        if let (1, Some(Value::Integer(uid))) = (dictionary.len(), dictionary.get(UID_KEY)) {
+            if let Ok(uid) = u64::try_from(*uid) {
+                return Ok(Value::Uid(uid));
+            }
+        }
+
+        Ok(Value::Dictionary(dictionary))
+    }
+
+    fn parse_array(&mut self) -> Result<Value, Error> {
+        self.enter()?;
+        let mut array = Vec::new();
+
+        loop {
+            match self.next_significant()? {
+                Token::End("array") => break,
+                token => array.push(self.parse_value(token)?),
+            }
+        }
+
+        self.depth -= 1;
+
+        Ok(Value::Array(array))
+    }
+
+    /// Collects the text content of element `name` up to its end tag.
+    fn parse_text(&mut self, name: &str, empty: bool) -> Result<String, Error> {
+        let mut text = String::new();
+
+        if empty {
+            return Ok(text);
+        }
+
+        loop {
+            match self.tokenizer.next_token()? {
+                Some(Token::Text(chunk)) => text.push_str(&chunk),
+                Some(Token::End(end)) if end == name => return Ok(text),
+                Some(_) => return Err(self.error(format!("unexpected element in `<{name}>`"))),
+                None => return Err(self.error(format!("unterminated `<{name}>`"))),
+            }
+        }
+    }
+
+    fn expect_end(&mut self, name: &str) -> Result<(), Error> {
+        match self.next_significant()? {
+            Token::End(end) if end == name => Ok(()),
+            _ => Err(self.error(format!("expected `</{name}>`"))),
+        }
+    }
+}
+
+fn unescape(text: &str, position: usize) -> Result<String, Error> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        let end = rest
+            .find(';')
+            .ok_or_else(|| Error::xml(position, "unterminated entity reference"))?;
+        let entity = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let char = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+
+                code.and_then(char::from_u32)
+                    .ok_or_else(|| Error::xml(position, format!("unknown entity `&{entity};`")))?
+            }
+        };
+
+        result.push(char);
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn parse_integer(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    let magnitude = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i128>().ok()?,
+    };
+
+    i64::try_from(if negative { -magnitude } else { magnitude }).ok()
+}
+
+fn parse_real(text: &str) -> Option<f64> {
+    match text.to_ascii_lowercase().as_str() {
+        "nan" => Some(f64::NAN),
+        "inf" | "infinity" | "+infinity" | "+inf" => Some(f64::INFINITY),
+        "-inf" | "-infinity" => Some(f64::NEG_INFINITY),
+        _ => text.parse().ok(),
+    }
+}
+
+fn format_real(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 {
+            "+infinity"
+        } else {
+            "-infinity"
+        }
+        .to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                result.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+
+    result
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(text.len() / 4 * 3);
+    let mut group = 0u32;
+    let mut count = 0;
+    let mut padding = 0;
+
+    for byte in text.bytes() {
+        let sextet = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            _ if byte.is_ascii_whitespace() => continue,
+            _ => return None,
+        };
+
+        if padding > 0 {
+            return None;
+        }
+
+        group = group << 6 | u32::from(sextet);
+        count += 1;
+
+        if count == 4 {
+            result.extend_from_slice(&group.to_be_bytes()[1..]);
+            group = 0;
+            count = 0;
+        }
+    }
+
+    match count {
+        0 => {}
+        2 => result.push((group >> 4) as u8),
+        3 => result.extend_from_slice(&((group >> 2) as u16).to_be_bytes()),
+        _ => return None,
+    }
+
+    Some(result)
+}
+
+pub(crate) fn write(mut writer: impl Write, value: &Value) -> Result<(), Error> {
+    let mut xml = String::from(HEADER);
+    write_value(&mut xml, value, 0);
+    xml.push_str("</plist>\n");
+
+    writer.write_all(xml.as_bytes())?;
+
+    Ok(())
+}
+
+fn write_value(xml: &mut String, value: &Value, depth: usize) {
+    let indent = "\t".repeat(depth);
+
+    match value {
+        Value::Array(array) if array.is_empty() => xml.push_str(&format!("{indent}<array/>\n")),
+        Value::Array(array) => {
+            xml.push_str(&format!("{indent}<array>\n"));
+            for child in array {
+                write_value(xml, child, depth + 1);
+            }
+            xml.push_str(&format!("{indent}</array>\n"));
+        }
+        Value::Dictionary(dictionary) if dictionary.is_empty() => {
+            xml.push_str(&format!("{indent}<dict/>\n"))
+        }
+        Value::Dictionary(dictionary) => {
+            xml.push_str(&format!("{indent}<dict>\n"));
+            for (key, child) in dictionary {
+                xml.push_str(&format!("{indent}\t<key>{}</key>\n", escape(key)));
+                write_value(xml, child, depth + 1);
+            }
+            xml.push_str(&format!("{indent}</dict>\n"));
+        }
+        Value::Boolean(true) => xml.push_str(&format!("{indent}<true/>\n")),
+        Value::Boolean(false) => xml.push_str(&format!("{indent}<false/>\n")),
+        Value::Data(data) => {
+            xml.push_str(&format!("{indent}<data>\n"));
+            for line in base64_encode(data).as_bytes().chunks(68) {
+                xml.push_str(&indent);
+                xml.push_str(std::str::from_utf8(line).unwrap());
+                xml.push('\n');
+            }
+            xml.push_str(&format!("{indent}</data>\n"));
+        }
+        Value::Date(date) => {
+            xml.push_str(&format!("{indent}<date>{}</date>\n", format_iso8601(*date)))
+        }
+        Value::Real(real) => {
+            xml.push_str(&format!("{indent}<real>{}</real>\n", format_real(*real)))
+        }
+        Value::Integer(integer) => xml.push_str(&format!("{indent}<integer>{integer}</integer>\n")),
+        Value::String(string) => {
+            xml.push_str(&format!("{indent}<string>{}</string>\n", escape(string)))
+        }
+        Value::Uid(uid) => {
+            xml.push_str(&format!("{indent}<dict>\n"));
+            xml.push_str(&format!("{indent}\t<key>{UID_KEY}</key>\n"));
+            xml.push_str(&format!("{indent}\t<integer>{uid}</integer>\n"));
+            xml.push_str(&format!("{indent}</dict>\n"));
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plist::{from_bytes, to_bytes, Format};
+
+    const INFO_PLIST: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/plist/Info.plist"
+    ));
+
+    #[test]
+    fn reads_info_plist() {
+        let info = read(INFO_PLIST).unwrap();
+
+        assert_eq!(
+            info.get_string("CFBundleIdentifier"),
+            Some("com.apple.TextEdit")
+        );
+        assert_eq!(
+            info.get_string("NSHumanReadableCopyright"),
+            Some("Copyright © 1995-2023 Apple Inc. All rights reserved.")
+        );
+        assert_eq!(
+            info.get("NSSupportsSuddenTermination"),
+            Some(&Value::Boolean(false))
+        );
+
+        let document_types = info.get("CFBundleDocumentTypes").unwrap();
+        let extensions = document_types.as_array().unwrap()[0]
+            .get("CFBundleTypeExtensions")
+            .unwrap();
+        assert_eq!(
+            extensions,
+            &Value::Array(vec![
+                Value::String("txt".to_string()),
+                Value::String("text".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn round_trips_info_plist() {
+        let info = read(INFO_PLIST).unwrap();
+
+        for format in [Format::Xml, Format::Binary] {
+            let bytes = to_bytes(&info, format).unwrap();
+            assert_eq!(Format::detect(&bytes), format);
+            assert_eq!(from_bytes(&bytes).unwrap(), info);
+        }
+    }
+
+    #[test]
+    fn round_trips_every_value_type() {
+        let value = Value::Dictionary(Dictionary::from([
+            ("data".to_string(), Value::Data((0..=255).collect())),
+            (
+                "date".to_string(),
+                Value::Date(std::time::UNIX_EPOCH + std::time::Duration::from_secs(978_307_200)),
+            ),
+            ("empty".to_string(), Value::Array(Vec::new())),
+            ("escaped".to_string(), Value::String("<a & b>".to_string())),
+            ("integer".to_string(), Value::Integer(-42)),
+            ("real".to_string(), Value::Real(0.5)),
+            ("uid".to_string(), Value::Uid(7)),
+        ]));
+
+        let bytes = to_bytes(&value, Format::Xml).unwrap();
+        assert_eq!(read(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        for document in [
+            "",
+            "<plist version=\"1.0\"><dict><key>a</key></dict></plist>",
+            "<plist version=\"1.0\"><array><string>a</string></plist>",
+            "<plist version=\"1.0\"><integer>twelve</integer></plist>",
+            "<plist version=\"1.0\"><string>&bogus;</string></plist>",
+        ] {
+            assert!(read(document.as_bytes()).is_err(), "{document}");
+        }
+    }
+}
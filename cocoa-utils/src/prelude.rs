@@ -5,12 +5,19 @@ pub use cocoa_utils_procmacro::CocoaType;
 
 pub use crate::object::*;
 
+pub use crate::CocoaError;
+
+pub use crate::Array;
+pub use crate::Data;
+pub use crate::Date;
 pub use crate::Dict;
 pub use crate::NS_String;
 pub use crate::NS_int;
 pub use crate::NS_uint;
+pub use crate::Notification;
 pub use crate::NotificationCenter;
 pub use crate::NotificationType;
+pub use crate::Number;
 pub use crate::RunningApplication;
 pub use crate::Workspace;
 pub use crate::URL;
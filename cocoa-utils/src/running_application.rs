@@ -1,4 +1,7 @@
-use cocoa::{appkit::NSImage, base::YES, foundation::NSData};
+use std::time::SystemTime;
+
+use cocoa::appkit::NSImage;
+use cocoa::base::{BOOL, YES};
 
 use crate::prelude::*;
 
@@ -12,6 +15,37 @@ impl RunningApplication {
     #[cocoa_type_property(NSRunningApplication, currentApplication)]
     pub unsafe fn current_application() -> RunningApplication {}
 
+    /// Returns the running application with the given process identifier.
+    pub fn with_process_identifier(pid: usize) -> Option<RunningApplication> {
+        unsafe {
+            let pid = pid as libc::pid_t;
+            let application: Id = msg_send![
+                class!(NSRunningApplication),
+                runningApplicationWithProcessIdentifier: pid
+            ];
+            RunningApplication::from_ptr(application)
+        }
+    }
+
+    /// Returns every running instance of the application with the given bundle identifier.
+    pub fn with_bundle_identifier(bundle_identifier: &str) -> Vec<RunningApplication> {
+        unsafe {
+            let bundle_identifier = NS_String::from(bundle_identifier);
+            let applications: Id = msg_send![
+                class!(NSRunningApplication),
+                runningApplicationsWithBundleIdentifier: bundle_identifier.ptr()
+            ];
+
+            match Array::from_ptr(applications) {
+                Some(applications) => applications
+                    .iter()
+                    .filter_map(|application| RunningApplication::from_ptr(application))
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+    }
+
     #[cocoa_instance_property(active)]
     pub unsafe fn active(&self) -> bool {}
 
@@ -33,6 +67,9 @@ impl RunningApplication {
     #[cocoa_instance_property(executableURL)]
     pub unsafe fn executable_url(&self) -> Option<URL> {}
 
+    #[cocoa_instance_property(launchDate)]
+    pub unsafe fn launch_date(&self) -> Option<SystemTime> {}
+
     #[cocoa_instance_property(isFinishedLaunching)]
     pub unsafe fn is_finished_launching(&self) -> bool {}
 
@@ -45,15 +82,37 @@ impl RunningApplication {
     #[cocoa_instance_property(icon)]
     pub unsafe fn icon(&self) -> Id {}
 
-    pub unsafe fn save_icon_to_file(&self, path: &str) -> bool {
-        if std::path::Path::new(path).exists() {
-            return false;
-        }
+    /// Hides the application. Returns `false` if the request couldn't be made, e.g. because the
+    /// application has terminated.
+    pub fn hide(&self) -> bool {
+        let hidden: BOOL = unsafe { msg_send![self.ptr, hide] };
+        hidden == YES
+    }
+
+    /// Unhides the application, without activating it.
+    pub fn unhide(&self) -> bool {
+        let unhidden: BOOL = unsafe { msg_send![self.ptr, unhide] };
+        unhidden == YES
+    }
 
-        let path = NS_String::from(path);
+    /// Brings the application to the front, even if the current application isn't active.
+    pub fn activate(&self) -> bool {
+        // NSApplicationActivateIgnoringOtherApps
+        let options: NS_uint = 1 << 1;
+        let activated: BOOL = unsafe { msg_send![self.ptr, activateWithOptions: options] };
+        activated == YES
+    }
+
+    /// Returns the application's icon encoded as TIFF.
+    pub unsafe fn icon_tiff_representation(&self) -> Option<Data> {
         let icon = self.icon();
-        let data = icon.TIFFRepresentation();
+        Data::from_ptr(icon.TIFFRepresentation())
+    }
 
-        data.writeToFile_atomically_(path.ptr(), YES) == YES
+    /// Saves the application's icon as a TIFF file at `path`, failing if the file already exists.
+    pub unsafe fn save_icon_to_file(&self, path: &str) -> Result<(), CocoaError> {
+        self.icon_tiff_representation()
+            .ok_or_else(|| CocoaError::nil_return("TIFFRepresentation"))?
+            .write_to_file(path, false)
     }
 }
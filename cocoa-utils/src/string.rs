@@ -34,3 +34,18 @@ impl ToString for NS_String {
         c_str.to_string_lossy().into_owned()
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NS_String {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NS_String {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Ok(NS_String::from(&string))
+    }
+}
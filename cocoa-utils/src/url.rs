@@ -1,3 +1,11 @@
+use std::ffi::{CStr, CString, OsStr};
+use std::fmt;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use cocoa::base::{nil, BOOL};
+
 use crate::prelude::*;
 
 #[allow(clippy::upper_case_acronyms)]
@@ -29,14 +37,36 @@ impl URL {
     #[cocoa_instance_property(path)]
     pub unsafe fn path(&self) -> String {}
 
-    // #[cocoa_instance_property(pathComponents)]
-    // pub unsafe fn path_components(&self) -> Vec<String> {}
+    pub unsafe fn path_components(&self) -> Vec<String> {
+        let components: Id = msg_send![self.ptr(), pathComponents];
+
+        if components.is_null() {
+            return Vec::new();
+        }
+
+        let count: usize = msg_send![components, count];
+
+        (0..count)
+            .map(|i| {
+                let component: Id = msg_send![components, objectAtIndex: i];
+                NS_String::from_ptr(component).unwrap().to_string()
+            })
+            .collect()
+    }
 
     #[cocoa_instance_property(pathExtension)]
     pub unsafe fn path_extension(&self) -> String {}
 
-    // #[cocoa_instance_property(port)]
-    // pub unsafe fn port(&self) -> Option<usize> {}
+    pub unsafe fn port(&self) -> Option<u16> {
+        let port: Id = msg_send![self.ptr(), port];
+
+        if port.is_null() {
+            None
+        } else {
+            let port: u16 = msg_send![port, unsignedShortValue];
+            Some(port)
+        }
+    }
 
     #[cocoa_instance_property(query)]
     pub unsafe fn query(&self) -> Option<String> {}
@@ -61,4 +91,118 @@ impl URL {
 
     #[cocoa_instance_property(password)]
     pub unsafe fn password(&self) -> Option<String> {}
+
+    #[cocoa_instance_property(isFileURL)]
+    pub unsafe fn is_file_url(&self) -> bool {}
+}
+
+impl URL {
+    /// Parses `string` as a URL. Fails if `NSURL` rejects it as malformed.
+    pub fn parse(string: &str) -> Result<URL, CocoaError> {
+        unsafe {
+            let string = NS_String::from(string);
+            let ptr: Id = msg_send![class!(NSURL), URLWithString: string.ptr()];
+
+            URL::from_ptr(ptr).ok_or_else(|| CocoaError::nil_return("URLWithString:"))
+        }
+    }
+
+    /// Creates a file URL for `path`. Relative paths are resolved against the current directory.
+    ///
+    /// The path's bytes are passed through unchanged, so paths that aren't valid UTF-8 are
+    /// preserved. Fails if the path contains a NUL byte.
+    pub fn from_file_path(path: &Path) -> Result<URL, CocoaError> {
+        const SELECTOR: &str = "fileURLWithFileSystemRepresentation:isDirectory:relativeToURL:";
+
+        let representation = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| CocoaError::invalid_argument("the path contains a NUL byte"))?;
+        let is_directory: BOOL = path.is_dir().into();
+
+        unsafe {
+            let path = representation.as_ptr();
+            let ptr: Id = msg_send![class!(NSURL), fileURLWithFileSystemRepresentation:path
+                                                                           isDirectory:is_directory
+                                                                         relativeToURL:nil];
+
+            URL::from_ptr(ptr).ok_or_else(|| CocoaError::nil_return(SELECTOR))
+        }
+    }
+
+    /// Returns a new URL with `component` appended to the path. Fails if `NSURL` can't append
+    /// it, e.g. to a URL without a path.
+    pub fn join(&self, component: &str) -> Result<URL, CocoaError> {
+        unsafe {
+            let component = NS_String::from(component);
+            let ptr: Id = msg_send![self.ptr(), URLByAppendingPathComponent: component.ptr()];
+
+            URL::from_ptr(ptr).ok_or_else(|| CocoaError::nil_return("URLByAppendingPathComponent:"))
+        }
+    }
+
+    /// Converts a file URL to a path, or returns `None` if this isn't a file URL.
+    pub fn to_file_path(&self) -> Option<PathBuf> {
+        unsafe {
+            if !self.is_file_url() {
+                return None;
+            }
+
+            let representation: *const libc::c_char =
+                msg_send![self.ptr(), fileSystemRepresentation];
+
+            if representation.is_null() {
+                return None;
+            }
+
+            let bytes = CStr::from_ptr(representation).to_bytes();
+            Some(PathBuf::from(OsStr::from_bytes(bytes)))
+        }
+    }
+}
+
+impl FromStr for URL {
+    type Err = CocoaError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        URL::parse(string)
+    }
+}
+
+impl fmt::Display for URL {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        unsafe { f.write_str(&self.absolute_string()) }
+    }
+}
+
+#[cfg(feature = "url")]
+impl TryFrom<&URL> for ::url::Url {
+    type Error = ::url::ParseError;
+
+    fn try_from(url: &URL) -> Result<Self, Self::Error> {
+        unsafe { ::url::Url::parse(&url.absolute_string()) }
+    }
+}
+
+#[cfg(feature = "url")]
+impl TryFrom<&::url::Url> for URL {
+    type Error = CocoaError;
+
+    fn try_from(url: &::url::Url) -> Result<Self, Self::Error> {
+        URL::parse(url.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for URL {
+    /// Serializes the URL as its absolute string.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        unsafe { serializer.serialize_str(&self.absolute_string()) }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for URL {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        URL::parse(&string).map_err(serde::de::Error::custom)
+    }
 }
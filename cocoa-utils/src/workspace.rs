@@ -15,4 +15,19 @@ impl Workspace {
 
     #[cocoa_instance_property(frontmostApplication)]
     pub unsafe fn frontmost_application(&self) -> RunningApplication {}
+
+    /// Returns the applications running in the user's session, including background agents.
+    pub fn running_applications(&self) -> Vec<RunningApplication> {
+        unsafe {
+            let applications: Id = msg_send![self.ptr, runningApplications];
+
+            match Array::from_ptr(applications) {
+                Some(applications) => applications
+                    .iter()
+                    .filter_map(|application| RunningApplication::from_ptr(application))
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+    }
 }